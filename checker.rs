@@ -1,47 +1,456 @@
-use std::collections::{hash_map::Entry, HashMap, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::convert::{TryFrom, TryInto};
+use std::hash::{Hash, Hasher};
 use std::io::{BufRead, BufReader};
+use std::rc::Rc;
+use std::sync::Arc;
 
-#[derive(Clone)]
+// counts allocations so that calibrate can show how much the interpreter
+// leans on the allocator. only in checkers built with
+// `--cfg count_allocations`, everything else gets the system allocator as is
+#[cfg(count_allocations)]
+struct CountingAllocator;
+
+#[cfg(count_allocations)]
+static ALLOCATIONS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+#[cfg(count_allocations)]
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        std::alloc::System.alloc(layout)
+    }
+    unsafe fn alloc_zeroed(&self, layout: std::alloc::Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        std::alloc::System.alloc_zeroed(layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        std::alloc::System.realloc(ptr, layout, new_size)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(ptr, layout)
+    }
+}
+
+#[cfg(count_allocations)]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[cfg(count_allocations)]
+fn allocations() -> Option<usize> {
+    Some(ALLOCATIONS.load(std::sync::atomic::Ordering::Relaxed))
+}
+
+#[cfg(not(count_allocations))]
+fn allocations() -> Option<usize> {
+    None
+}
+
+// identifiers are interned once per process, so operands hold ids that are
+// cheap to copy and compare, and variables can live in a vector indexed by
+// them instead of a map keyed by name
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct Symbol(u32);
+
+#[derive(Default)]
+struct Interner {
+    names: Vec<&'static str>,
+    ids: HashMap<&'static str, Symbol>,
+}
+
+static INTERNER: std::sync::OnceLock<std::sync::Mutex<Interner>> = std::sync::OnceLock::new();
+
+thread_local! {
+    // each thread's copy of the interned names and nested operands, so that
+    // looking one up only takes the lock the first time the thread sees it
+    static NAMES: std::cell::RefCell<Vec<&'static str>> = Default::default();
+    static NESTED_OPERANDS: std::cell::RefCell<Vec<Operand>> = Default::default();
+}
+
+impl Symbol {
+    fn intern(name: &str) -> Symbol {
+        let mut interner = INTERNER.get_or_init(Default::default).lock().unwrap();
+        if let Some(&symbol) = interner.ids.get(name) {
+            return symbol;
+        }
+        // there are only as many names as distinct identifiers ever parsed
+        let name: &'static str = Box::leak(name.into());
+        let symbol = Symbol(interner.names.len() as u32);
+        interner.names.push(name);
+        interner.ids.insert(name, symbol);
+        symbol
+    }
+    fn name(self) -> &'static str {
+        NAMES.with(|names| {
+            let mut names = names.borrow_mut();
+            if self.index() >= names.len() {
+                let interner = INTERNER.get().unwrap().lock().unwrap();
+                let known = names.len();
+                names.extend_from_slice(&interner.names[known..]);
+            }
+            names[self.index()]
+        })
+    }
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// one of the two indices of a grid cell
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Index {
+    Constant(i64),
+    Variable(Symbol),
+    Nested(Nested),
+}
+
+// an element used as an index, like the b[i] in a[b[i]], interned like
+// identifiers so operands stay Copy however deep they nest
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct Nested(u32);
+
+#[derive(Default)]
+struct NestedInterner {
+    operands: Vec<Operand>,
+    ids: HashMap<Operand, Nested>,
+}
+
+static NESTED: std::sync::OnceLock<std::sync::Mutex<NestedInterner>> = std::sync::OnceLock::new();
+
+impl Nested {
+    fn intern(operand: Operand) -> Nested {
+        let mut interner = NESTED.get_or_init(Default::default).lock().unwrap();
+        if let Some(&nested) = interner.ids.get(&operand) {
+            return nested;
+        }
+        let nested = Nested(interner.operands.len() as u32);
+        interner.operands.push(operand);
+        interner.ids.insert(operand, nested);
+        nested
+    }
+    fn get(self) -> Operand {
+        NESTED_OPERANDS.with(|operands| {
+            let mut operands = operands.borrow_mut();
+            if self.0 as usize >= operands.len() {
+                let interner = NESTED.get().unwrap().lock().unwrap();
+                let known = operands.len();
+                operands.extend_from_slice(&interner.operands[known..]);
+            }
+            operands[self.0 as usize]
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 enum Operand {
     Constant(i64),
-    Variable(String),
-    ArrayConstIndex(String, usize),
-    ArrayVarIndex(String, String),
+    Variable(Symbol),
+    ArrayConstIndex(Symbol, i64),
+    ArrayVarIndex(Symbol, Symbol),
+    ArrayNestedIndex(Symbol, Nested),
+    GridIndex(Symbol, Index, Index),
+    // a string literal, interned like identifiers so operands stay Copy
+    Text(Symbol),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy)]
+enum Operator {
+    Plus,
+    Minus,
+}
+
+#[derive(Clone, Copy)]
 enum Instruction {
     Nop(),
     Input(Operand),
+    InputBulk(Operand),
+    InputChar(Operand),
     Output(Operand),
+    OutputChar(Operand),
+    DebugOutput(Operand),
     Assign(Operand, Operand),
+    Swap(Operand, Operand),
+    CondAssign(Operand, Operand, Operand),
+    Random(Operand, Operand),
+    Max(Operand, Operand, Operand),
+    Min(Operand, Operand, Operand),
+    Evaluate(Operand, Operand, Operator, Operand),
     Add(Operand, Operand),
     Sub(Operand, Operand),
+    Mul(Operand, Operand),
+    Div(Operand, Operand),
+    Mod(Operand, Operand),
+    ShiftLeft(Operand, Operand),
+    ShiftRight(Operand, Operand),
+    ShiftRightUnsigned(Operand, Operand),
+    Neg(Operand),
+    Abs(Operand),
+    Append(Operand, Operand),
+    Sort(Operand),
+    Sum(Operand, Operand, Option<Operand>),
+    Slice(Operand, Operand, Operand),
+    Concat(Operand, Operand),
+    Length(Operand, Operand),
     Compare(Operand, Operand),
+    CompareEq(Operand, Operand),
+    CompareLt(Operand, Operand),
+    CompareLe(Operand, Operand),
+    CompareNe(Operand, Operand),
     Jump(Operand),
+    Call(Operand),
+    ReturnSub(),
     Return(),
+    Assert(Operand),
+    Abort(Operand),
+    Custom(OpcodeId, Operands),
 }
 
-#[derive(Clone, Debug)]
+// custom opcodes get an id in a registry of the whole process, so that
+// instructions only carry the id and stay Copy. the registry doesn't keep
+// them alive, the builders and programs using them do, and the id of one
+// nothing uses anymore goes to the next one registered
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct OpcodeId(u32);
+
+static OPCODES: std::sync::OnceLock<std::sync::Mutex<Vec<std::sync::Weak<Opcode>>>> =
+    std::sync::OnceLock::new();
+
+impl OpcodeId {
+    fn register(opcode: &Arc<Opcode>) -> OpcodeId {
+        let mut opcodes = OPCODES.get_or_init(Default::default).lock().unwrap();
+        let weak = Arc::downgrade(opcode);
+        match opcodes.iter().position(|slot| slot.strong_count() == 0) {
+            Some(index) => {
+                opcodes[index] = weak;
+                OpcodeId(index as u32)
+            }
+            None => {
+                opcodes.push(weak);
+                OpcodeId(opcodes.len() as u32 - 1)
+            }
+        }
+    }
+    fn get(self) -> Arc<Opcode> {
+        OPCODES.get().unwrap().lock().unwrap()[self.0 as usize]
+            .upgrade()
+            .expect("opcode outlived every program using it")
+    }
+}
+
+// the operands of a custom opcode, stored inline instead of in a Vec
+#[derive(Clone, Copy)]
+struct Operands {
+    len: u8,
+    items: [Operand; Operands::CAPACITY],
+}
+
+impl Operands {
+    // any more and custom opcodes would be the biggest instruction
+    const CAPACITY: usize = 2;
+    fn as_slice(&self) -> &[Operand] {
+        &self.items[..self.len as usize]
+    }
+}
+
+impl std::iter::FromIterator<Operand> for Operands {
+    fn from_iter<I: IntoIterator<Item = Operand>>(iter: I) -> Self {
+        let mut operands = Operands {
+            len: 0,
+            items: [Operand::Constant(0); Operands::CAPACITY],
+        };
+        for operand in iter {
+            operands.items[operands.len as usize] = operand;
+            operands.len += 1;
+        }
+        operands
+    }
+}
+
+type OpcodeFn = dyn Fn(&mut Program, &[Operand]) -> Result<(), Verdict> + Send + Sync;
+
+// an instruction defined by whoever embeds the interpreter, written as a
+// pattern of tokens where each `_` stands for an operand
+struct Opcode {
+    pattern: Vec<String>,
+    cost: usize,
+    execute: Box<OpcodeFn>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 enum Variable {
     Integer(i64),
     Array(Vec<i64>),
+    Text(String),
+    // rows of integers, usually all the same length
+    Grid(Vec<Vec<i64>>),
+}
+
+#[derive(Clone, Debug, Default)]
+struct ProgramMetadata {
+    author: Option<String>,
+    task: Option<i32>,
+    version: Option<u32>,
+    features: Vec<String>,
 }
 
 #[derive(Clone, Default)]
-struct Program {
+struct InputChannel {
+    values: Vec<Variable>,
+    position: usize,
+    // the values as the characters of their input text, for `nibble`, and
+    // how many values that text covers
+    stream: Vec<char>,
+    stream_values: usize,
+    stream_position: usize,
+}
+
+// everything about a program that stays the same while it runs, shared by
+// every execution of it, including ones on other threads
+#[derive(Clone, Default)]
+struct CompiledProgram {
     instructions: Vec<Instruction>,
     costs: Vec<usize>,
-    variables: HashMap<String, Variable>,
-    input: VecDeque<Variable>,
-    output: VecDeque<Variable>,
+    metadata: ProgramMetadata,
+    // the source line of every instruction, empty when they're the same
+    source_lines: Vec<usize>,
+    source_len: usize,
+    // the custom opcodes the program was compiled with, kept alive for it
+    opcodes: Vec<(OpcodeId, Arc<Opcode>)>,
+    // runtime errors don't show values that could come from the tests
+    judging: bool,
+}
+
+impl CompiledProgram {
+    // checks what can be checked once instead of on every run, so that
+    // jumping can't fail at runtime unless the target isn't a constant
+    fn lower(&self) -> Result<(), Verdict> {
+        let len = self.instructions.len();
+        for (pc, instruction) in self.instructions.iter().enumerate() {
+            if let Some(target) = instruction.constant_target() {
+                if target < 1 || target > len as i64 {
+                    return Err(Verdict::CompileError(
+                        pc,
+                        format!(
+                            "`{}` goes nowhere, there are only {} lines",
+                            instruction, len
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+    // lines past the end keep their distance from it
+    fn source_line(&self, pc: usize) -> usize {
+        match self.source_lines.get(pc) {
+            Some(&line) => line,
+            None if self.source_lines.is_empty() => pc,
+            None => self.source_len + (pc - self.instructions.len()),
+        }
+    }
+    // errors are reported on the line of the source they came from
+    fn remap(&self, verdict: Verdict) -> Verdict {
+        match verdict {
+            Verdict::RuntimeError(pc, message) => {
+                Verdict::RuntimeError(self.source_line(pc), message)
+            }
+            Verdict::CompileError(pc, message) => {
+                Verdict::CompileError(self.source_line(pc), message)
+            }
+            verdict => verdict,
+        }
+    }
+}
+
+const _: fn() = || {
+    fn assert_thread_safe<T: Send + Sync>() {}
+    assert_thread_safe::<Arc<CompiledProgram>>();
+};
+
+// every change to how much the variables take goes through here, so the
+// usage is exact and its peak is known
+#[derive(Clone, Copy, Default)]
+struct MemoryUsage {
+    used: usize,
+    peak: usize,
+}
+
+impl MemoryUsage {
+    // in bytes, counting the name, eight bytes per integer and one per byte
+    // of text
+    fn footprint(name: &str, variable: &Variable) -> usize {
+        name.len()
+            + match variable {
+                Variable::Integer(_) => 8,
+                Variable::Array(values) => 8 * values.len(),
+                Variable::Text(text) => text.len(),
+                Variable::Grid(rows) => rows.iter().map(|row| 8 * row.len()).sum(),
+            }
+    }
+    fn allocate(&mut self, bytes: usize) {
+        self.used += bytes;
+        self.peak = self.peak.max(self.used);
+    }
+    fn free(&mut self, bytes: usize) {
+        self.used -= bytes;
+    }
+}
+
+#[derive(Clone, Default)]
+struct Program {
+    code: Arc<CompiledProgram>,
+    variables: Rc<Vec<Option<Variable>>>,
+    memory: MemoryUsage,
+    input: InputChannel,
+    output: Vec<Variable>,
+    output_position: usize,
+    debug_output: Vec<(usize, Variable)>,
+    // characters from `spit`, which become one text value in the output at
+    // the next `yeet` or when the program finishes. it only grows, so the
+    // part that is already in the output is remembered by its length
+    spat: String,
+    spat_flushed: usize,
     runtime: usize,
+    // of the run in progress, for intrinsics that meter themselves
+    time_limit: usize,
     pc: usize,
+    // where each `im back` goes, innermost call last
+    call_stack: Vec<usize>,
     returned: bool,
+    read_input: bool,
+    printed_before_input: bool,
+    // what `gamble` draws from, seeded by the test
+    rng: Pcg128,
 }
 
-#[derive(Debug)]
+#[derive(Clone)]
+struct Snapshot {
+    variables: Rc<Vec<Option<Variable>>>,
+    memory: MemoryUsage,
+    input: (usize, usize, usize),
+    output_len: usize,
+    output_position: usize,
+    debug_output_len: usize,
+    spat_len: usize,
+    spat_flushed: usize,
+    runtime: usize,
+    pc: usize,
+    call_stack: Vec<usize>,
+    returned: bool,
+    read_input: bool,
+    printed_before_input: bool,
+    rng: Pcg128,
+}
+
+#[derive(Clone, Debug)]
 enum Verdict {
     Correct(),
     WrongAnswer(String),
@@ -54,6 +463,23 @@ enum Verdict {
 
 struct CheckerFail(String);
 
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.into_iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+// lowercase words joined by dashes, for file names and test names
+fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 fn compress(string: &str) -> String {
     const LIMIT: usize = 32;
     let prefix = string.chars().take(LIMIT + 1);
@@ -73,13 +499,557 @@ where
     }
 }
 
+impl Verdict {
+    fn code(&self) -> &'static str {
+        use Verdict::*;
+        match self {
+            Correct() => "AC",
+            WrongAnswer(_) => "WA",
+            TimeLimitExceeded() => "TLE",
+            RuntimeError(_, _) => "RE",
+            CompileError(_, _) => "CE",
+            Based() => "BASED",
+            OtherError(_) => "FAIL",
+        }
+    }
+    fn message(&self) -> &str {
+        use Verdict::*;
+        match self {
+            WrongAnswer(message)
+            | RuntimeError(_, message)
+            | CompileError(_, message)
+            | OtherError(message) => message,
+            _ => "",
+        }
+    }
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use Verdict::*;
+        match self {
+            Correct() => write!(f, "ur the GOAT of based code!!1!"),
+            WrongAnswer(message) => write!(f, "this ain't it, chief, {}", message),
+            TimeLimitExceeded() => write!(f, "you have skill issue on speed smh"),
+            RuntimeError(line, message) => write!(
+                f,
+                "ya code got L + ratioed on line {} because {}",
+                line + 1,
+                message
+            ),
+            CompileError(line, message) => write!(
+                f,
+                "jesse, what are you talking about on line {}? {}",
+                line + 1,
+                message
+            ),
+            Based() => write!(
+                f,
+                r#""Based"? Are you kidding me? I spent a decent portion of my life preparing this problem and your submission to it is "Based"? What do I have to say to you? Absolutely nothing. I couldn't be bothered to respond to such meaningless attempt at writing code. Do you want "Based" on your Codeforces profile?"#,
+            ),
+            // polygon does weird things...
+            OtherError(message) => {
+                write!(f, "unexpected error in participant output: {}", message)
+            }
+        }
+    }
+}
+
+// what verdicts mean, for `explain`. an empty message is the entry for the
+// verdict code itself, the others are for specific messages
+struct VerdictDoc {
+    code: &'static str,
+    message: &'static str,
+    description: &'static str,
+    causes: &'static str,
+    example: &'static str,
+}
+
+const VERDICT_DOCS: &[VerdictDoc] = &[
+    VerdictDoc {
+        code: "AC",
+        message: "",
+        description: "the program printed the right answer on every test",
+        causes: "being the GOAT",
+        example: "yoink a\nyoink b\n*slaps a on top of b*\nyeet b\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "WA",
+        message: "",
+        description: "the program finished but printed the wrong thing on some test",
+        causes: "a wrong answer, printing more than one value, or printing an array",
+        example: "yoink a\nyoink b\nyeet a\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "WA",
+        message: "git gud",
+        description: "the program printed exactly one integer, but not the answer",
+        causes: "a bug in the solution, or a test case it didn't think about",
+        example: "yoink a\nyoink b\nyeet a\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "WA",
+        message: "too much stuff printed",
+        description: "the program printed more than one value",
+        causes: "a `yeet` inside a loop, or debug prints that should be `lowkey yeet`",
+        example: "yoink a\nyeet a\nyeet a\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "WA",
+        message: "U PRINTERD AN ENTRIE ARRAY???",
+        description: "the first value printed was a whole array",
+        causes: "yeeting an array instead of one of its elements",
+        example: "yoink n\nyoink a\nyeet a\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "WA",
+        message: "yeet the whole array, not a number",
+        description: "the answer is an array, but the program printed a single integer",
+        causes: "yeeting one element, or the length, instead of the array itself",
+        example: "",
+    },
+    VerdictDoc {
+        code: "WA",
+        message: "git gud at index",
+        description: "the printed array differs from the answer, first at the given index, and is missing some elements or has extra ones if the counts say so",
+        causes: "an off by one, or a bug that only shows up at some positions",
+        example: "",
+    },
+    VerdictDoc {
+        code: "WA",
+        message: "rows, found",
+        description: "the printed grid has a different number of rows than the answer",
+        causes: "hopping one row too many or too few onto the grid",
+        example: "",
+    },
+    VerdictDoc {
+        code: "WA",
+        message: "print something",
+        description: "the program finished without printing anything",
+        causes: "returning before the `yeet`, or forgetting it",
+        example: "yoink a\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "TLE",
+        message: "",
+        description: "the total cost of the executed lines went over the time limit",
+        causes: "a loop that never ends, or one that runs too many times; every executed line costs its length plus 5, so long lines inside loops add up",
+        example: "simp for 1",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "",
+        description: "the program crashed while running",
+        causes: "reading past the end of the input, using a variable before setting it, indexing out of bounds, or running off the end of the program",
+        example: "yeet x",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "you're reading from nothing",
+        description: "a `yoink` ran after all of the input was already read",
+        causes: "reading more values than the input has, like reading n values when the input has n-1, or a `yoink` inside a loop that runs once too often",
+        example: "yoink a\nyoink b\nyoink c\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "you're printing nothing",
+        description: "a `yeet` printed a variable that was never set",
+        causes: "a typo in the variable name, or setting it on a path that didn't run",
+        example: "yoink a\nyeet b\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "no such variable",
+        description: "an instruction used a variable that was never set",
+        causes: "a typo in the variable name, or reading it before the line that sets it",
+        example: "yoink a\n*slaps b on top of a*\nyeet a\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "out of bounds",
+        description: "an array was indexed outside of its elements, which are numbered from 0",
+        causes: "an off by one in a loop, or indexing with n instead of n-1",
+        example: "yoink n\nyoink a\nyeet a[n]\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "is negative, arrays start at 0",
+        description: "an array or grid was indexed with a negative number",
+        causes: "a constant index like `a[-1]` or `a[0-1]`, or a variable that went below 0",
+        example: "yoink n\nyoink a\nyeet a[-1]\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "that's not even a line",
+        description: "the program ran past its last line, or jumped somewhere that doesn't exist",
+        causes: "a missing `go touch some grass` at the end, or a `vibe check` on the last line",
+        example: "yoink a\nyeet a",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "goes nowhere",
+        description: "a `simp for` or `brb` with a variable target jumped to a line that doesn't exist",
+        causes: "a jump table indexed with a value that was never checked, or lines numbered from 0",
+        example: "bruh t is lowkey just 0\nsimp for t\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "nobody said brb",
+        description: "an `im back` ran without a `brb` to go back to",
+        causes: "falling through into a subroutine instead of jumping over it, or one `im back` too many",
+        example: "im back",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "brbs deep and nobody came back",
+        description: "too many `brb`s ran without an `im back`, and the call stack is full",
+        causes: "recursion that never reaches its base case, or using `brb` where `simp for` was meant",
+        example: "brb 1",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "was cap",
+        description: "a `no cap` assertion saw a 0, only outside of judging",
+        causes: "whatever the assertion was supposed to catch",
+        example: "no cap 0",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "rage quit:",
+        description: "the program stopped itself with `rage quit`, the rest is what it said",
+        causes: "an invariant the program checks for itself didn't hold",
+        example: "rage quit \"bye\"",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "you're dividing by zero",
+        description: "a `nerf` or `leftovers` was given 0 as the divisor",
+        causes: "a divisor read from the input that can be 0, or a loop that divides by its counter before incrementing it",
+        example: "bruh a is lowkey just 1\nnerf a by 0\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "you can only slide by 0 to 63 bits",
+        description: "a `slide` was given a negative amount, or one that would shift out every bit",
+        causes: "sliding by a value read from the input, or a counter that went one step too far",
+        example: "bruh a is lowkey just 1\nslide a left by 64\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "isn't a character",
+        description: "a `spit` was given a number that isn't a unicode code point",
+        causes: "spitting a digit's value instead of its character, which is the digit plus 48",
+        example: "spit -5",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "that was already nibbled",
+        description: "a `yoink` read from input that `nibble` had started reading character by character",
+        causes: "mixing the two ways of reading; pick `yoink` for values or `nibble` for characters",
+        example: "nibble c\nyoink a\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "that was already yoinked",
+        description: "a `nibble` read from input that `yoink` had started reading value by value",
+        causes: "mixing the two ways of reading; pick `yoink` for values or `nibble` for characters",
+        example: "yoink a\nnibble c\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "has no length",
+        description: "a `length check` was given an integer, which isn't an array or text",
+        causes: "checking the length of n instead of the array a, or a variable that was overwritten",
+        example: "bruh n is lowkey just 1\nlength check m on n\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "RE",
+        message: "can't gamble up to",
+        description: "`gamble` was given a bound below 1, so there's no number it could roll",
+        causes: "rolling an index into an empty array, or a bound that was decremented too far",
+        example: "gamble x up to 0",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "",
+        description: "some line isn't an instruction, or an operand can't be parsed",
+        causes: "typos in keywords, a missing `*` after `*slaps`, or `fr fr` directives after the first instruction; `explain <instruction>` shows how each one is written",
+        example: "vibe check a",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "unknown expression",
+        description: "a line doesn't match any instruction",
+        causes: "a typo in a keyword, or a missing operand",
+        example: "vibe check a",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "cannot parse operand",
+        description: "an operand isn't an integer, a variable or an array element",
+        causes: "a variable name starting with a digit, or a missing `]`",
+        example: "yeet 1a",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "cannot parse index",
+        description: "an array index isn't an integer, a variable or an element",
+        causes: "an expression with a variable like `a[i+1]`, which needs its own line",
+        example: "yeet a[i+1]",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "grids only go two deep",
+        description: "an operand has more than two indices, but grids only have rows and columns",
+        causes: "indexing a grid like a 3d array, or a stray `[]`",
+        example: "yeet g[0][0][0]",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "string literal never ends",
+        description: "a `\"` that starts a string literal has no `\"` after it to end it",
+        causes: "a missing closing quote",
+        example: "yeet \"oops",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "directives only go at the top",
+        description: "a `fr fr` directive came after the first instruction",
+        causes: "adding a directive at the end of the program instead of the start",
+        example: "yeet 1\nfr fr author me",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "unknown directive",
+        description: "a `fr fr` directive has a key the checker doesn't know",
+        causes: "a typo in the key",
+        example: "fr fr authr me",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "goes nowhere",
+        description: "a `simp for` or `brb` with a constant target jumps to a line that doesn't exist",
+        causes: "lines numbered from 0, or a jump that wasn't updated after removing lines",
+        example: "simp for 2",
+    },
+    VerdictDoc {
+        code: "CE",
+        message: "is banned in",
+        description: "the program uses an instruction that the task doesn't allow",
+        causes: "an instruction that would make the task trivial; `plan` lists the banned ones",
+        example: "yoink a\ngood vibes only a\nyeet a\ngo touch some grass",
+    },
+    VerdictDoc {
+        code: "BASED",
+        message: "",
+        description: "the program contains the word \"based\", in any case, anywhere",
+        causes: "writing \"based\"",
+        example: "yeet based",
+    },
+    VerdictDoc {
+        code: "FAIL",
+        message: "",
+        description: "something went wrong on the judge's side, not in the program",
+        causes: "a broken test pack or jury solution; tell whoever runs the contest",
+        example: "",
+    },
+];
+
+impl VerdictDoc {
+    // a verdict code gets its general entry, anything else the entries
+    // whose message shows up in the query or the other way around
+    fn lookup(query: &str) -> Vec<&'static VerdictDoc> {
+        let query = query.trim().to_lowercase();
+        if let Some(doc) = VERDICT_DOCS
+            .iter()
+            .find(|doc| doc.message.is_empty() && doc.code.to_lowercase() == query)
+        {
+            return vec![doc];
+        }
+        VERDICT_DOCS
+            .iter()
+            .filter(|doc| {
+                let message = doc.message.to_lowercase();
+                !query.is_empty()
+                    && !message.is_empty()
+                    && (query.contains(&message) || message.contains(&query))
+            })
+            .collect()
+    }
+}
+
+impl VerdictDoc {
+    // the tasks whose tests give the example this verdict. assertions are on,
+    // since `no cap` only fails outside of judging
+    fn tasks(&self) -> Result<Vec<i32>, CheckerFail> {
+        let lines = self.example.lines().map(String::from).collect::<Vec<_>>();
+        let mut tasks = Vec::new();
+        for task in TASKS {
+            let plan = test_plan(task, DEFAULT_SEED)?;
+            let verdict = match InterpreterBuilder::new()
+                .time_limit(plan.time_limit)
+                .task(task)
+                .build(&lines)
+            {
+                Ok(mut interpreter) => plan.judge(&mut interpreter, &VerdictPrecedence::First),
+                Err(compile_error) => compile_error,
+            };
+            if verdict.code() == self.code && verdict.message().contains(self.message) {
+                tasks.push(task);
+            }
+        }
+        Ok(tasks)
+    }
+}
+
+impl std::fmt::Display for VerdictDoc {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.message {
+            "" => writeln!(f, "{}", self.code)?,
+            message => writeln!(f, "{}: {}", self.code, message)?,
+        }
+        writeln!(f, "  means: {}", self.description)?;
+        write!(f, "  usually because: {}", self.causes)?;
+        if !self.example.is_empty() {
+            write!(f, "\n  for example:")?;
+            for line in self.example.lines() {
+                write!(f, "\n    {}", line)?;
+            }
+        }
+        if self.message.is_empty() {
+            let messages = VERDICT_DOCS
+                .iter()
+                .filter(|doc| doc.code == self.code && !doc.message.is_empty())
+                .map(|doc| doc.message)
+                .collect::<Vec<_>>();
+            if !messages.is_empty() {
+                write!(f, "\n  messages: {}", messages.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Variable {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Variable::Integer(value) => write!(f, "{}", value),
+            Variable::Array(values) => write!(f, "{:?}", values),
+            Variable::Text(text) => write!(f, "{}", text),
+            Variable::Grid(rows) => write!(f, "{:?}", rows),
+        }
+    }
+}
+
+impl Variable {
+    fn kind(&self) -> &'static str {
+        match self {
+            Variable::Integer(_) => "integer",
+            Variable::Array(_) => "array",
+            Variable::Text(_) => "text",
+            Variable::Grid(_) => "grid",
+        }
+    }
+}
+
+impl std::fmt::Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Operand::Constant(value) => write!(f, "{}", value),
+            Operand::Variable(var) => write!(f, "{}", var),
+            Operand::ArrayConstIndex(array, index) => write!(f, "{}[{}]", array, index),
+            Operand::ArrayVarIndex(array, index) => write!(f, "{}[{}]", array, index),
+            Operand::ArrayNestedIndex(array, index) => write!(f, "{}[{}]", array, index.get()),
+            Operand::GridIndex(grid, row, col) => write!(f, "{}[{}][{}]", grid, row, col),
+            Operand::Text(text) => write!(f, "\"{}\"", text),
+        }
+    }
+}
+
+impl std::fmt::Display for Index {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Index::Constant(index) => write!(f, "{}", index),
+            Index::Variable(var) => write!(f, "{}", var),
+            Index::Nested(index) => write!(f, "{}", index.get()),
+        }
+    }
+}
+
 impl TryFrom<&str> for Operand {
     type Error = String;
     fn try_from(string: &str) -> Result<Self, Self::Error> {
-        fn parse_array_index(string: &str) -> Option<(&str, &str)> {
-            let (part1, part23) = string.split_once('[')?;
-            let (part2, part3) = part23.split_once(']')?;
-            part3.is_empty().then_some((part1, part2))
+        // `a[i]` or `g[i][j]`, where an index can have brackets of its own
+        fn parse_indices(string: &str) -> Option<(&str, Vec<&str>)> {
+            let (name, _) = string.split_once('[')?;
+            let mut rest = &string[name.len()..];
+            let mut indices = Vec::new();
+            while !rest.is_empty() {
+                rest = rest.strip_prefix('[')?;
+                let mut depth = 1;
+                let end = rest.find(|c| {
+                    match c {
+                        '[' => depth += 1,
+                        ']' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })?;
+                indices.push(&rest[..end]);
+                rest = &rest[end + 1..];
+            }
+            Some((name, indices))
+        }
+        // integers like `-1` or `+1`, or `+`, `-` and `*` on them like `2+3*4`, folded right here
+        fn constant(string: &str) -> Result<Option<i64>, String> {
+            fn factor(string: &str) -> Option<Option<i64>> {
+                let (sign, digits) = match (string.strip_prefix('-'), string.strip_prefix('+')) {
+                    (Some(digits), _) => (-1, digits),
+                    (None, Some(digits)) => (1, digits),
+                    (None, None) => (1, string),
+                };
+                if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                Some(digits.bytes().try_fold(0i64, |value, c| {
+                    value.checked_mul(10)?.checked_add(sign * (c - b'0') as i64)
+                }))
+            }
+            fn term(string: &str) -> Option<Option<i64>> {
+                string.split('*').try_fold(Some(1i64), |product, string| {
+                    Some(factor(string)?.and_then(|value| product?.checked_mul(value)))
+                })
+            }
+            // a `-` right at the start or after another operator is a sign, not a minus
+            let mut terms = Vec::new();
+            let mut start = 0;
+            let mut minus = false;
+            for (i, c) in string.char_indices() {
+                let after_operator = i == 0 || string[..i].ends_with(['+', '-', '*']);
+                if (c == '+' || c == '-') && !after_operator {
+                    terms.push((minus, &string[start..i]));
+                    (start, minus) = (i + 1, c == '-');
+                }
+            }
+            terms.push((minus, &string[start..]));
+            let mut sum = Some(0i64);
+            for (minus, string) in terms {
+                let Some(value) = term(string) else {
+                    return Ok(None);
+                };
+                sum = match (sum, value) {
+                    (Some(sum), Some(value)) if minus => sum.checked_sub(value),
+                    (Some(sum), Some(value)) => sum.checked_add(value),
+                    _ => None,
+                };
+            }
+            match sum {
+                Some(value) => Ok(Some(value)),
+                None => Err(format!(
+                    "'{}' doesn't fit in 64 bits, touch grass with smaller numbers",
+                    compress(string)
+                )),
+            }
         }
         fn is_identifier(string: &str) -> bool {
             let mut chars = string.chars();
@@ -88,17 +1058,37 @@ impl TryFrom<&str> for Operand {
                 .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
                 && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
         }
-        if let Some((array, index)) = parse_array_index(string) {
+        let parse_index = |index: &str| {
+            if let Some(value) = constant(index)? {
+                Ok(Index::Constant(value))
+            } else if is_identifier(index) {
+                Ok(Index::Variable(Symbol::intern(index)))
+            } else if index.contains('[') && !index.starts_with('"') {
+                Ok(Index::Nested(Nested::intern(Operand::try_from(index)?)))
+            } else {
+                Err(format!(
+                    "cannot parse index '{}', should be integer, identifier or element",
+                    compress(index)
+                ))
+            }
+        };
+        if let Some((array, indices)) = parse_indices(string) {
             if is_identifier(array) {
-                if let Ok(value) = index.parse() {
-                    Ok(Operand::ArrayConstIndex(array.to_string(), value))
-                } else if is_identifier(index) {
-                    Ok(Operand::ArrayVarIndex(array.to_string(), index.to_string()))
-                } else {
-                    Err(format!(
-                        "cannot parse index '{}', should be integer or identifier",
-                        compress(index)
-                    ))
+                let array = Symbol::intern(array);
+                let indices = indices
+                    .into_iter()
+                    .map(parse_index)
+                    .collect::<Result<Vec<_>, _>>()?;
+                match indices[..] {
+                    [Index::Constant(index)] => Ok(Operand::ArrayConstIndex(array, index)),
+                    [Index::Variable(index)] => Ok(Operand::ArrayVarIndex(array, index)),
+                    [Index::Nested(index)] => Ok(Operand::ArrayNestedIndex(array, index)),
+                    [row, col] => Ok(Operand::GridIndex(array, row, col)),
+                    _ => Err(format!(
+                        "'{}' has {} indices, grids only go two deep",
+                        compress(string),
+                        indices.len()
+                    )),
                 }
             } else {
                 Err(format!(
@@ -107,126 +1097,1490 @@ impl TryFrom<&str> for Operand {
                     compress(array)
                 ))
             }
-        } else if let Ok(value) = string.parse() {
+        } else if let Some(text) = string
+            .strip_prefix('"')
+            .and_then(|string| string.strip_suffix('"'))
+        {
+            Ok(Operand::Text(Symbol::intern(text)))
+        } else if let Some(value) = constant(string)? {
             Ok(Operand::Constant(value))
         } else if is_identifier(string) {
-            Ok(Operand::Variable(string.to_string()))
+            Ok(Operand::Variable(Symbol::intern(string)))
         } else {
             Err(format!(
                 "cannot parse operand '{}', should be one of: \
-                integer, identifier, identifier[integer], identifier[identifier]",
+                integer, identifier, identifier[integer], identifier[identifier], \
+                identifier[element], identifier[index][index]",
                 compress(string)
             ))
         }
     }
 }
 
-impl TryFrom<&str> for Instruction {
-    type Error = String;
-    fn try_from(string: &str) -> Result<Self, Self::Error> {
-        use Instruction::*;
-        let tokens = string.split_whitespace().collect::<Vec<_>>();
-        match tokens[..] {
-            [] => Ok(Nop()),
-            ["yoink", dst] => Ok(Input(dst.try_into()?)),
-            ["yeet", src] => Ok(Output(src.try_into()?)),
-            ["bruh", dst, "is", "lowkey", "just", src] => {
-                Ok(Assign(dst.try_into()?, src.try_into()?))
-            }
-            ["*slaps", src, "on", "top", "of", dst] if dst.ends_with('*') => {
-                Ok(Add(dst[..dst.len() - 1].try_into()?, src.try_into()?))
+// the code before a `// comment`, which costs nothing. a `//` inside a
+// string literal is part of the literal
+fn strip_comment(line: &str) -> &str {
+    let mut in_literal = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_literal = !in_literal,
+            '/' if !in_literal && line[index..].starts_with("//") => {
+                return line[..index].trim_end()
             }
-            ["rip", "this", dst, "fell", "off", "by", src] => {
-                Ok(Sub(dst.try_into()?, src.try_into()?))
-            }
-            ["vibe", "check", dst, "ratios", src] => Ok(Compare(dst.try_into()?, src.try_into()?)),
-            ["simp", "for", src] => Ok(Jump(src.try_into()?)),
-            ["go", "touch", "some", "grass"] => Ok(Return()),
-            _ => Err(format!("unknown expression: '{}'", compress(string))),
+            _ => {}
         }
     }
+    line
 }
 
-impl Program {
-    const INSTRUCTION_BASE_COST: usize = 5;
-    fn compile(lines: &[String]) -> Result<Program, Verdict> {
-        let mut prog = Program::default();
-        for (lineno, line) in lines.iter().enumerate() {
-            if line.to_lowercase().find("based").is_some() {
-                return Err(Verdict::Based());
-            }
-            match line.as_str().try_into() {
-                Ok(instruction) => {
-                    prog.instructions.push(instruction);
-                    prog.costs.push(line.len() + Self::INSTRUCTION_BASE_COST);
-                }
-                Err(message) => {
-                    return Err(Verdict::CompileError(lineno, message));
-                }
-            }
+// splits a line on whitespace, except inside "string literals", which stay
+// one token with their quotes
+fn tokenize(line: &str) -> Result<Vec<&str>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let end = match rest.strip_prefix('"') {
+            Some(literal) => match literal.find('"') {
+                Some(end) => end + 2,
+                None => return Err("string literal never ends, close it with a \"".to_string()),
+            },
+            None => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+        };
+        tokens.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    Ok(tokens)
+}
+
+impl std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Operator::Plus => write!(f, "+"),
+            Operator::Minus => write!(f, "-"),
         }
-        Ok(prog)
     }
-    fn get_int_mut(&mut self, var_name: &str) -> Result<&mut i64, Verdict> {
-        match self.variables.get_mut(var_name) {
-            Some(Variable::Integer(value)) => Ok(value),
-            Some(Variable::Array(_)) => Err(Verdict::RuntimeError(
-                self.pc,
-                format!("expected integer, found array {}", compress(var_name)),
-            )),
-            None => Err(Verdict::RuntimeError(
-                self.pc,
-                format!("no such variable {}", compress(var_name)),
+}
+
+impl TryFrom<&str> for Operator {
+    type Error = String;
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        match string {
+            "+" => Ok(Operator::Plus),
+            "-" => Ok(Operator::Minus),
+            _ => Err(format!(
+                "cannot parse operator '{}', should be + or -",
+                compress(string)
             )),
         }
     }
-    fn get_int_mut_or_default(&mut self, var_name: &str) -> Result<&mut i64, Verdict> {
-        match self.variables.entry(var_name.to_string()) {
-            Entry::Occupied(entry) => match entry.into_mut() {
-                Variable::Integer(value) => Ok(value),
-                Variable::Array(_) => Err(Verdict::RuntimeError(
-                    self.pc,
-                    format!("expected integer, found array {}", compress(var_name)),
-                )),
-            },
-            Entry::Vacant(entry) => match entry.insert(Variable::Integer(0)) {
-                Variable::Integer(value) => Ok(value),
-                _ => unreachable!(),
-            },
+}
+
+impl Operator {
+    fn apply(self, lhs: i64, rhs: i64) -> i64 {
+        match self {
+            Operator::Plus => lhs + rhs,
+            Operator::Minus => lhs - rhs,
         }
     }
-    fn get_arr_mut(&mut self, var_name: &str) -> Result<&mut [i64], Verdict> {
-        match self.variables.get_mut(var_name) {
-            Some(Variable::Array(value)) => Ok(value),
-            Some(Variable::Integer(_)) => Err(Verdict::RuntimeError(
-                self.pc,
-                format!("expected array, found integer {}", compress(var_name)),
-            )),
-            None => Err(Verdict::RuntimeError(
-                self.pc,
-                format!("no such variable {}", compress(var_name)),
-            )),
+}
+
+impl Operand {
+    // the operand itself followed by every element used inside its brackets
+    fn flatten(&self) -> Vec<Operand> {
+        let mut operands = vec![*self];
+        let nested = match self {
+            Operand::ArrayNestedIndex(_, index) => vec![*index],
+            Operand::GridIndex(_, row, col) => [row, col]
+                .iter()
+                .filter_map(|index| match index {
+                    Index::Nested(index) => Some(*index),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        for index in nested {
+            operands.extend(index.get().flatten());
         }
+        operands
     }
-    fn get_value(&mut self, operand: &Operand) -> Result<i64, Verdict> {
-        match operand {
-            Operand::Constant(value) => Ok(*value),
-            Operand::Variable(var) => self.get_int_mut(var).copied(),
-            Operand::ArrayConstIndex(array, index) => {
-                let lineno = self.pc;
-                let array = self.get_arr_mut(array)?;
-                array.get(*index).copied().ok_or_else(|| {
-                    Verdict::RuntimeError(lineno, format!("index {} out of bounds", index))
+
+    fn map_identifiers(&self, rename: &mut impl FnMut(&str) -> String) -> Operand {
+        let nested =
+            |index: &Nested, rename: &mut _| Nested::intern(index.get().map_identifiers(rename));
+        let mut f = |symbol: &Symbol| Symbol::intern(&rename(symbol.name()));
+        match self {
+            Operand::Constant(value) => Operand::Constant(*value),
+            Operand::Variable(var) => Operand::Variable(f(var)),
+            Operand::ArrayConstIndex(array, index) => Operand::ArrayConstIndex(f(array), *index),
+            Operand::ArrayVarIndex(array, index) => Operand::ArrayVarIndex(f(array), f(index)),
+            Operand::ArrayNestedIndex(array, index) => {
+                let array = f(array);
+                Operand::ArrayNestedIndex(array, nested(index, rename))
+            }
+            Operand::GridIndex(grid, row, col) => {
+                let grid = f(grid);
+                let mut index = |index: &Index| match index {
+                    Index::Constant(index) => Index::Constant(*index),
+                    Index::Variable(var) => Index::Variable(Symbol::intern(&rename(var.name()))),
+                    Index::Nested(index) => Index::Nested(nested(index, rename)),
+                };
+                Operand::GridIndex(grid, index(row), index(col))
+            }
+            Operand::Text(text) => Operand::Text(*text),
+        }
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use Instruction::*;
+        match self {
+            Nop() => Ok(()),
+            Input(dst) => write!(f, "yoink {}", dst),
+            InputBulk(dst) => write!(f, "yoink {} in bulk", dst),
+            InputChar(dst) => write!(f, "nibble {}", dst),
+            Output(src) => write!(f, "yeet {}", src),
+            OutputChar(src) => write!(f, "spit {}", src),
+            DebugOutput(src) => write!(f, "lowkey yeet {}", src),
+            Assign(dst, src) => write!(f, "bruh {} is lowkey just {}", dst, src),
+            Swap(a, b) => write!(f, "switcheroo {} and {}", a, b),
+            Random(dst, bound) => write!(f, "gamble {} up to {}", dst, bound),
+            Max(dst, a, b) => write!(f, "goat {} between {} and {}", dst, a, b),
+            Min(dst, a, b) => write!(f, "flop {} between {} and {}", dst, a, b),
+            CondAssign(dst, src, cond) => {
+                write!(f, "bruh {} is lowkey just {} if {}", dst, src, cond)
+            }
+            Evaluate(dst, lhs, op, rhs) => {
+                write!(f, "bruh {} is lowkey just {} {} {}", dst, lhs, op, rhs)
+            }
+            Add(dst, src) => write!(f, "*slaps {} on top of {}*", src, dst),
+            Sub(dst, src) => write!(f, "rip this {} fell off by {}", dst, src),
+            Mul(dst, src) => write!(f, "stonks {} by {}", dst, src),
+            Div(dst, src) => write!(f, "nerf {} by {}", dst, src),
+            Mod(dst, src) => write!(f, "leftovers {} by {}", dst, src),
+            ShiftLeft(dst, src) => write!(f, "slide {} left by {}", dst, src),
+            ShiftRight(dst, src) => write!(f, "slide {} right by {}", dst, src),
+            ShiftRightUnsigned(dst, src) => write!(f, "slide {} right unsigned by {}", dst, src),
+            Neg(dst) => write!(f, "uno reverse {}", dst),
+            Abs(dst) => write!(f, "good vibes only {}", dst),
+            Append(dst, src) => write!(f, "hop on {} with {}", dst, src),
+            Sort(dst) => write!(f, "sort it out {}", dst),
+            Sum(dst, array, None) => write!(f, "stack up {} from {}", dst, array),
+            Sum(dst, start, Some(end)) => {
+                write!(f, "stack up {} from {} to {}", dst, start, end)
+            }
+            Slice(dst, start, end) => write!(f, "copypasta {} from {} to {}", dst, start, end),
+            Concat(dst, src) => write!(f, "glue {} with {}", dst, src),
+            Length(dst, src) => write!(f, "length check {} on {}", dst, src),
+            Compare(dst, src) => write!(f, "vibe check {} ratios {}", dst, src),
+            CompareEq(dst, src) => write!(f, "vibe check {} twinning {}", dst, src),
+            CompareLt(dst, src) => write!(f, "vibe check {} ratioed by {}", dst, src),
+            CompareLe(dst, src) => write!(f, "vibe check {} cant ratio {}", dst, src),
+            CompareNe(dst, src) => write!(f, "vibe check {} not twinning {}", dst, src),
+            Jump(dst) => write!(f, "simp for {}", dst),
+            Call(dst) => write!(f, "brb {}", dst),
+            ReturnSub() => write!(f, "im back"),
+            Return() => write!(f, "go touch some grass"),
+            Assert(src) => write!(f, "no cap {}", src),
+            Abort(src) => write!(f, "rage quit {}", src),
+            Custom(opcode, operands) => {
+                let opcode = opcode.get();
+                let mut operands = operands.as_slice().iter();
+                let tokens = opcode.pattern.iter().map(|token| match token.as_str() {
+                    "_" => operands.next().unwrap().to_string(),
+                    _ => token.clone(),
+                });
+                write!(f, "{}", tokens.collect::<Vec<_>>().join(" "))
+            }
+        }
+    }
+}
+
+impl Instruction {
+    fn map_operands(&self, mut f: impl FnMut(&Operand) -> Operand) -> Instruction {
+        use Instruction::*;
+        match self {
+            Nop() => Nop(),
+            Input(a) => Input(f(a)),
+            InputBulk(a) => InputBulk(f(a)),
+            InputChar(a) => InputChar(f(a)),
+            Output(a) => Output(f(a)),
+            OutputChar(a) => OutputChar(f(a)),
+            DebugOutput(a) => DebugOutput(f(a)),
+            Assign(a, b) => Assign(f(a), f(b)),
+            Swap(a, b) => Swap(f(a), f(b)),
+            Random(a, b) => Random(f(a), f(b)),
+            Max(a, b, c) => Max(f(a), f(b), f(c)),
+            Min(a, b, c) => Min(f(a), f(b), f(c)),
+            CondAssign(a, b, c) => CondAssign(f(a), f(b), f(c)),
+            Evaluate(a, b, op, c) => Evaluate(f(a), f(b), *op, f(c)),
+            Add(a, b) => Add(f(a), f(b)),
+            Sub(a, b) => Sub(f(a), f(b)),
+            Mul(a, b) => Mul(f(a), f(b)),
+            Div(a, b) => Div(f(a), f(b)),
+            Mod(a, b) => Mod(f(a), f(b)),
+            ShiftLeft(a, b) => ShiftLeft(f(a), f(b)),
+            ShiftRight(a, b) => ShiftRight(f(a), f(b)),
+            ShiftRightUnsigned(a, b) => ShiftRightUnsigned(f(a), f(b)),
+            Neg(a) => Neg(f(a)),
+            Abs(a) => Abs(f(a)),
+            Append(a, b) => Append(f(a), f(b)),
+            Sort(a) => Sort(f(a)),
+            Sum(a, b, c) => Sum(f(a), f(b), c.as_ref().map(f)),
+            Slice(a, b, c) => Slice(f(a), f(b), f(c)),
+            Concat(a, b) => Concat(f(a), f(b)),
+            Length(a, b) => Length(f(a), f(b)),
+            Compare(a, b) => Compare(f(a), f(b)),
+            CompareEq(a, b) => CompareEq(f(a), f(b)),
+            CompareLt(a, b) => CompareLt(f(a), f(b)),
+            CompareLe(a, b) => CompareLe(f(a), f(b)),
+            CompareNe(a, b) => CompareNe(f(a), f(b)),
+            Jump(a) => Jump(f(a)),
+            Call(a) => Call(f(a)),
+            ReturnSub() => ReturnSub(),
+            Return() => Return(),
+            Assert(a) => Assert(f(a)),
+            Abort(a) => Abort(f(a)),
+            Custom(opcode, operands) => {
+                Custom(*opcode, operands.as_slice().iter().map(f).collect())
+            }
+        }
+    }
+    fn operands(&self) -> Vec<&Operand> {
+        use Instruction::*;
+        match self {
+            Nop() | Return() | ReturnSub() => vec![],
+            Input(a) | InputBulk(a) | InputChar(a) | Output(a) | OutputChar(a) | DebugOutput(a)
+            | Jump(a) | Call(a) | Assert(a) | Abort(a) | Sort(a) | Neg(a) | Abs(a) => vec![a],
+            Assign(a, b)
+            | Swap(a, b)
+            | Random(a, b)
+            | Add(a, b)
+            | Sub(a, b)
+            | Mul(a, b)
+            | Div(a, b)
+            | Mod(a, b)
+            | ShiftLeft(a, b)
+            | ShiftRight(a, b)
+            | ShiftRightUnsigned(a, b)
+            | Append(a, b)
+            | Concat(a, b)
+            | Length(a, b)
+            | Compare(a, b)
+            | CompareEq(a, b)
+            | CompareLt(a, b)
+            | CompareLe(a, b)
+            | CompareNe(a, b) => vec![a, b],
+            Sum(a, b, None) => vec![a, b],
+            Evaluate(a, b, _, c)
+            | Sum(a, b, Some(c))
+            | CondAssign(a, b, c)
+            | Max(a, b, c)
+            | Min(a, b, c)
+            | Slice(a, b, c) => {
+                vec![a, b, c]
+            }
+            Custom(_, operands) => operands.as_slice().iter().collect(),
+        }
+    }
+    // the line a `simp for` or `brb` goes to, when it is a constant
+    fn constant_target(&self) -> Option<i64> {
+        match self {
+            Instruction::Jump(Operand::Constant(line))
+            | Instruction::Call(Operand::Constant(line)) => Some(*line),
+            _ => None,
+        }
+    }
+    // the same jump going to another line
+    fn retarget(&self, line: i64) -> Instruction {
+        match self {
+            Instruction::Call(_) => Instruction::Call(Operand::Constant(line)),
+            _ => Instruction::Jump(Operand::Constant(line)),
+        }
+    }
+    // the ones that skip the next line when they fail
+    fn is_vibe_check(&self) -> bool {
+        use Instruction::*;
+        matches!(
+            self,
+            Compare(..) | CompareEq(..) | CompareLt(..) | CompareLe(..) | CompareNe(..)
+        )
+    }
+}
+
+impl Opcode {
+    // None if the line doesn't look like this opcode at all
+    fn parse(&self, id: OpcodeId, tokens: &[&str]) -> Option<Result<Instruction, String>> {
+        if tokens.len() != self.pattern.len()
+            || tokens
+                .iter()
+                .zip(&self.pattern)
+                .any(|(token, pattern)| pattern != "_" && token != pattern)
+        {
+            return None;
+        }
+        let operands = tokens
+            .iter()
+            .zip(&self.pattern)
+            .filter(|(_, pattern)| *pattern == "_")
+            .map(|(&token, _)| token.try_into())
+            .collect::<Result<Operands, _>>();
+        Some(operands.map(|operands| Instruction::Custom(id, operands)))
+    }
+}
+
+impl TryFrom<&str> for Instruction {
+    type Error = String;
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        use Instruction::*;
+        // ranges of an array start at one of its elements, like `a[i]`
+        fn range_start(keyword: &str, start: &str) -> Result<Operand, String> {
+            match start.try_into()? {
+                start @ (Operand::ArrayConstIndex(..)
+                | Operand::ArrayVarIndex(..)
+                | Operand::ArrayNestedIndex(..)) => Ok(start),
+                _ => Err(format!(
+                    "cannot {} from '{}', start from an array element like a[i]",
+                    keyword,
+                    compress(start)
+                )),
+            }
+        }
+        let tokens = tokenize(string)?;
+        match tokens[..] {
+            [] => Ok(Nop()),
+            ["yoink", dst] => Ok(Input(dst.try_into()?)),
+            ["yoink", dst, "in", "bulk"] => Ok(InputBulk(dst.try_into()?)),
+            ["nibble", dst] => Ok(InputChar(dst.try_into()?)),
+            ["yeet", src] => Ok(Output(src.try_into()?)),
+            ["spit", src] => Ok(OutputChar(src.try_into()?)),
+            ["lowkey", "yeet", src] => Ok(DebugOutput(src.try_into()?)),
+            ["bruh", dst, "is", "lowkey", "just", src] => {
+                Ok(Assign(dst.try_into()?, src.try_into()?))
+            }
+            ["switcheroo", a, "and", b] => Ok(Swap(a.try_into()?, b.try_into()?)),
+            ["gamble", dst, "up", "to", bound] => Ok(Random(dst.try_into()?, bound.try_into()?)),
+            ["goat", dst, "between", a, "and", b] => {
+                Ok(Max(dst.try_into()?, a.try_into()?, b.try_into()?))
+            }
+            ["flop", dst, "between", a, "and", b] => {
+                Ok(Min(dst.try_into()?, a.try_into()?, b.try_into()?))
+            }
+            ["bruh", dst, "is", "lowkey", "just", src, "if", cond] => Ok(CondAssign(
+                dst.try_into()?,
+                src.try_into()?,
+                cond.try_into()?,
+            )),
+            ["bruh", dst, "is", "lowkey", "just", lhs, op, rhs] => Ok(Evaluate(
+                dst.try_into()?,
+                lhs.try_into()?,
+                op.try_into()?,
+                rhs.try_into()?,
+            )),
+            ["*slaps", src, "on", "top", "of", dst] if dst.ends_with('*') => {
+                Ok(Add(dst[..dst.len() - 1].try_into()?, src.try_into()?))
+            }
+            ["rip", "this", dst, "fell", "off", "by", src] => {
+                Ok(Sub(dst.try_into()?, src.try_into()?))
+            }
+            ["stonks", dst, "by", src] => Ok(Mul(dst.try_into()?, src.try_into()?)),
+            ["nerf", dst, "by", src] => Ok(Div(dst.try_into()?, src.try_into()?)),
+            ["leftovers", dst, "by", src] => Ok(Mod(dst.try_into()?, src.try_into()?)),
+            ["slide", dst, "left", "by", src] => Ok(ShiftLeft(dst.try_into()?, src.try_into()?)),
+            ["slide", dst, "right", "by", src] => Ok(ShiftRight(dst.try_into()?, src.try_into()?)),
+            ["slide", dst, "right", "unsigned", "by", src] => {
+                Ok(ShiftRightUnsigned(dst.try_into()?, src.try_into()?))
+            }
+            ["uno", "reverse", dst] => Ok(Neg(dst.try_into()?)),
+            ["good", "vibes", "only", dst] => Ok(Abs(dst.try_into()?)),
+            ["hop", "on", dst, "with", src] => Ok(Append(dst.try_into()?, src.try_into()?)),
+            ["sort", "it", "out", dst] => Ok(Sort(dst.try_into()?)),
+            ["copypasta", dst, "from", start, "to", end] => Ok(Slice(
+                dst.try_into()?,
+                range_start("copypasta", start)?,
+                end.try_into()?,
+            )),
+            ["stack", "up", dst, "from", array] => match array.try_into()? {
+                array @ Operand::Variable(_) => Ok(Sum(dst.try_into()?, array, None)),
+                _ => Err(format!(
+                    "cannot stack up '{}', it should be a whole array or start from an \
+                    element like a[i] to an index",
+                    compress(array)
+                )),
+            },
+            ["stack", "up", dst, "from", start, "to", end] => Ok(Sum(
+                dst.try_into()?,
+                range_start("stack up", start)?,
+                Some(end.try_into()?),
+            )),
+            ["glue", dst, "with", src] => Ok(Concat(dst.try_into()?, src.try_into()?)),
+            ["length", "check", dst, "on", src] => Ok(Length(dst.try_into()?, src.try_into()?)),
+            ["vibe", "check", dst, "ratios", src] => Ok(Compare(dst.try_into()?, src.try_into()?)),
+            ["vibe", "check", dst, "twinning", src] => {
+                Ok(CompareEq(dst.try_into()?, src.try_into()?))
+            }
+            ["vibe", "check", dst, "ratioed", "by", src] => {
+                Ok(CompareLt(dst.try_into()?, src.try_into()?))
+            }
+            ["vibe", "check", dst, "cant", "ratio", src] => {
+                Ok(CompareLe(dst.try_into()?, src.try_into()?))
+            }
+            ["vibe", "check", dst, "not", "twinning", src] => {
+                Ok(CompareNe(dst.try_into()?, src.try_into()?))
+            }
+            ["simp", "for", src] => Ok(Jump(src.try_into()?)),
+            ["brb", src] => Ok(Call(src.try_into()?)),
+            ["im", "back"] => Ok(ReturnSub()),
+            ["go", "touch", "some", "grass"] => Ok(Return()),
+            ["no", "cap", src] => Ok(Assert(src.try_into()?)),
+            ["rage", "quit", src] => Ok(Abort(src.try_into()?)),
+            _ => match &InstructionDoc::lookup(string)[..] {
+                [] => Err(format!("unknown expression: '{}'", compress(string))),
+                docs => Err(format!(
+                    "unknown expression: '{}', did u mean `{}`",
+                    compress(string),
+                    docs.iter()
+                        .map(|doc| doc.pattern)
+                        .collect::<Vec<_>>()
+                        .join("` or `")
+                )),
+            },
+        }
+    }
+}
+
+// what every instruction looks like, for `explain` and for suggestions in
+// compile errors. selftest parses each example and prints it back, so this
+// can't quietly drift away from the parser above
+struct InstructionDoc {
+    keyword: &'static str,
+    pattern: &'static str,
+    operands: &'static str,
+    semantics: &'static str,
+    // charged on top of the usual cost of a line
+    extra_cost: usize,
+    // charged as it runs, for instructions whose cost depends on the values
+    running_cost: &'static str,
+    example: &'static str,
+}
+
+const INSTRUCTION_DOCS: &[InstructionDoc] = &[
+    InstructionDoc {
+        keyword: "yoink",
+        pattern: "yoink <dst>",
+        operands: "dst is a variable",
+        semantics: "reads the next input value into dst",
+        extra_cost: 0,
+        running_cost: "",
+        example: "yoink n",
+    },
+    InstructionDoc {
+        keyword: "yoink",
+        pattern: "yoink <dst> in bulk",
+        operands: "dst is a variable",
+        semantics: "reads an integer count and then that many integers into dst as a \
+            new array",
+        extra_cost: 0,
+        running_cost: "1 per integer read",
+        example: "yoink a in bulk",
+    },
+    InstructionDoc {
+        keyword: "nibble",
+        pattern: "nibble <dst>",
+        operands: "dst is a variable or array element",
+        semantics:
+            "reads the next character of the input, written out as text, as its code point, \
+            or -1 at the end. an input can be nibbled or yoinked, not both",
+        extra_cost: 0,
+        running_cost: "",
+        example: "nibble c",
+    },
+    InstructionDoc {
+        keyword: "spit",
+        pattern: "spit <src>",
+        operands: "src is any operand, a code point",
+        semantics: "prints the character with code point src. characters spat in a row \
+            print as one text, which is judged like the output of a C++ solution",
+        extra_cost: 0,
+        running_cost: "",
+        example: "spit c",
+    },
+    InstructionDoc {
+        keyword: "yeet",
+        pattern: "yeet <src>",
+        operands: "src is any operand, or a \"string literal\" to print as text",
+        semantics: "prints src",
+        extra_cost: 0,
+        running_cost: "",
+        example: "yeet m",
+    },
+    InstructionDoc {
+        keyword: "lowkey yeet",
+        pattern: "lowkey yeet <src>",
+        operands: "src is any operand, or a \"string literal\" like a marker",
+        semantics: "prints src to the debug output, which is never judged",
+        extra_cost: 0,
+        running_cost: "",
+        example: "lowkey yeet a[i]",
+    },
+    InstructionDoc {
+        keyword: "bruh",
+        pattern: "bruh <dst> is lowkey just <src>",
+        operands: "dst is a variable or array element, src is any operand",
+        semantics: "sets dst to src",
+        extra_cost: 0,
+        running_cost: "",
+        example: "bruh m is lowkey just a[0]",
+    },
+    InstructionDoc {
+        keyword: "switcheroo",
+        pattern: "switcheroo <a> and <b>",
+        operands: "a and b are variables or array elements holding integers",
+        semantics: "swaps the values of a and b",
+        extra_cost: 0,
+        running_cost: "",
+        example: "switcheroo a[i] and a[j]",
+    },
+    InstructionDoc {
+        keyword: "gamble",
+        pattern: "gamble <dst> up to <bound>",
+        operands: "dst is a variable or array element, bound is any operand, at least 1",
+        semantics: "sets dst to a random integer from 0 to bound - 1. the numbers \
+            depend only on the test, so every run of it rolls the same ones",
+        extra_cost: 0,
+        running_cost: "",
+        example: "gamble pivot up to n",
+    },
+    InstructionDoc {
+        keyword: "goat",
+        pattern: "goat <dst> between <a> and <b>",
+        operands: "dst is a variable or array element, a and b are any operands",
+        semantics: "sets dst to the larger of a and b",
+        extra_cost: 0,
+        running_cost: "",
+        example: "goat best between best and a[i]",
+    },
+    InstructionDoc {
+        keyword: "flop",
+        pattern: "flop <dst> between <a> and <b>",
+        operands: "dst is a variable or array element, a and b are any operands",
+        semantics: "sets dst to the smaller of a and b",
+        extra_cost: 0,
+        running_cost: "",
+        example: "flop worst between worst and a[i]",
+    },
+    InstructionDoc {
+        keyword: "bruh",
+        pattern: "bruh <dst> is lowkey just <lhs> <op> <rhs>",
+        operands: "dst is a variable or array element, lhs and rhs are any operands, op is + or -",
+        semantics: "sets dst to lhs op rhs",
+        extra_cost: Program::INSTRUCTION_BASE_COST,
+        running_cost: "",
+        example: "bruh d is lowkey just a - b",
+    },
+    InstructionDoc {
+        keyword: "bruh",
+        pattern: "bruh <dst> is lowkey just <src> if <cond>",
+        operands: "dst is a variable or array element, src and cond are any operands",
+        semantics: "sets dst to src if cond isn't 0, and leaves it alone otherwise. \
+            never jumps, so it costs no more than a plain `bruh`",
+        extra_cost: 0,
+        running_cost: "",
+        example: "bruh m is lowkey just x if bigger",
+    },
+    InstructionDoc {
+        keyword: "*slaps",
+        pattern: "*slaps <src> on top of <dst>*",
+        operands: "dst is a variable or array element, src is any operand",
+        semantics: "adds src to dst",
+        extra_cost: 0,
+        running_cost: "",
+        example: "*slaps 1 on top of i*",
+    },
+    InstructionDoc {
+        keyword: "rip this",
+        pattern: "rip this <dst> fell off by <src>",
+        operands: "dst is a variable or array element, src is any operand",
+        semantics: "subtracts src from dst",
+        extra_cost: 0,
+        running_cost: "",
+        example: "rip this n fell off by 1",
+    },
+    InstructionDoc {
+        keyword: "stonks",
+        pattern: "stonks <dst> by <src>",
+        operands: "dst is a variable or array element, src is any operand",
+        semantics: "multiplies dst by src",
+        extra_cost: Program::INSTRUCTION_BASE_COST,
+        running_cost: "",
+        example: "stonks ans by 2",
+    },
+    InstructionDoc {
+        keyword: "nerf",
+        pattern: "nerf <dst> by <src>",
+        operands: "dst is a variable or array element, src is any operand",
+        semantics: "divides dst by src, rounding towards zero",
+        extra_cost: Program::INSTRUCTION_BASE_COST,
+        running_cost: "",
+        example: "nerf n by 10",
+    },
+    InstructionDoc {
+        keyword: "leftovers",
+        pattern: "leftovers <dst> by <src>",
+        operands: "dst is a variable or array element, src is any operand",
+        semantics: "sets dst to the remainder of dst divided by src, with the sign of dst",
+        extra_cost: Program::INSTRUCTION_BASE_COST,
+        running_cost: "",
+        example: "leftovers n by 10",
+    },
+    InstructionDoc {
+        keyword: "slide",
+        pattern: "slide <dst> left by <src>",
+        operands: "dst is a variable or array element, src is any operand from 0 to 63",
+        semantics: "shifts dst left by src bits",
+        extra_cost: 0,
+        running_cost: "",
+        example: "slide x left by 1",
+    },
+    InstructionDoc {
+        keyword: "slide",
+        pattern: "slide <dst> right by <src>",
+        operands: "dst is a variable or array element, src is any operand from 0 to 63",
+        semantics: "shifts dst right by src bits, keeping the sign",
+        extra_cost: 0,
+        running_cost: "",
+        example: "slide x right by 1",
+    },
+    InstructionDoc {
+        keyword: "slide",
+        pattern: "slide <dst> right unsigned by <src>",
+        operands: "dst is a variable or array element, src is any operand from 0 to 63",
+        semantics: "shifts dst right by src bits, filling in zeros",
+        extra_cost: 0,
+        running_cost: "",
+        example: "slide x right unsigned by 1",
+    },
+    InstructionDoc {
+        keyword: "uno reverse",
+        pattern: "uno reverse <dst>",
+        operands: "dst is a variable or array element",
+        semantics: "negates dst",
+        extra_cost: 0,
+        running_cost: "",
+        example: "uno reverse x",
+    },
+    InstructionDoc {
+        keyword: "good vibes only",
+        pattern: "good vibes only <dst>",
+        operands: "dst is a variable or array element",
+        semantics: "sets dst to its absolute value",
+        extra_cost: 0,
+        running_cost: "",
+        example: "good vibes only x",
+    },
+    InstructionDoc {
+        keyword: "hop on",
+        pattern: "hop on <dst> with <src>",
+        operands: "dst is an array variable, src is any operand or an array variable",
+        semantics: "appends src to the end of dst, or all of src if it is an array, \
+            making dst an empty array first if it doesn't exist",
+        extra_cost: 0,
+        running_cost: "1 per value appended",
+        example: "hop on ans with x",
+    },
+    InstructionDoc {
+        keyword: "sort it out",
+        pattern: "sort it out <dst>",
+        operands: "dst is an array variable",
+        semantics: "sorts dst from smallest to largest",
+        extra_cost: 0,
+        running_cost: "n·⌈log2 n⌉ for n values, as many comparisons as a merge sort makes",
+        example: "sort it out a",
+    },
+    InstructionDoc {
+        keyword: "stack up",
+        pattern: "stack up <dst> from <start> [to <end>]",
+        operands: "dst is a variable or array element, start is an array variable, \
+            or an array element if there is an end, end is any operand",
+        semantics: "sets dst to the sum of the array, or of start's array from start \
+            up to, but not including, index end",
+        extra_cost: 0,
+        running_cost: "1 per value added",
+        example: "stack up total from a[lo] to hi",
+    },
+    InstructionDoc {
+        keyword: "copypasta",
+        pattern: "copypasta <dst> from <start> to <end>",
+        operands: "dst is an array variable, start is an array element, \
+            end is any operand",
+        semantics: "sets dst to the values of start's array from start up to, but \
+            not including, index end",
+        extra_cost: 0,
+        running_cost: "1 per value copied",
+        example: "copypasta left from a[0] to mid",
+    },
+    InstructionDoc {
+        keyword: "glue",
+        pattern: "glue <dst> with <src>",
+        operands: "dst is a text variable, src is a text variable or any other operand",
+        semantics: "appends src to the end of dst, integers written out in decimal, \
+            making dst empty text first if it doesn't exist",
+        extra_cost: 0,
+        running_cost: "1 per character appended",
+        example: "glue s with t",
+    },
+    InstructionDoc {
+        keyword: "length check",
+        pattern: "length check <dst> on <src>",
+        operands: "dst is a variable or array element, src is an array or text variable",
+        semantics: "sets dst to the number of values in the array or characters in the text",
+        extra_cost: 0,
+        running_cost: "",
+        example: "length check n on s",
+    },
+    InstructionDoc {
+        keyword: "vibe check",
+        pattern: "vibe check <lhs> ratios <rhs>",
+        operands: "lhs and rhs are any operands",
+        semantics: "runs the next line only if lhs > rhs, otherwise skips it",
+        extra_cost: 0,
+        running_cost: "",
+        example: "vibe check n ratios i",
+    },
+    InstructionDoc {
+        keyword: "vibe check",
+        pattern: "vibe check <lhs> twinning <rhs>",
+        operands: "lhs and rhs are any operands",
+        semantics: "runs the next line only if lhs = rhs, otherwise skips it",
+        extra_cost: 0,
+        running_cost: "",
+        example: "vibe check n twinning i",
+    },
+    InstructionDoc {
+        keyword: "vibe check",
+        pattern: "vibe check <lhs> ratioed by <rhs>",
+        operands: "lhs and rhs are any operands",
+        semantics: "runs the next line only if lhs < rhs, otherwise skips it",
+        extra_cost: 0,
+        running_cost: "",
+        example: "vibe check i ratioed by n",
+    },
+    InstructionDoc {
+        keyword: "vibe check",
+        pattern: "vibe check <lhs> cant ratio <rhs>",
+        operands: "lhs and rhs are any operands",
+        semantics: "runs the next line only if lhs <= rhs, otherwise skips it",
+        extra_cost: 0,
+        running_cost: "",
+        example: "vibe check i cant ratio n",
+    },
+    InstructionDoc {
+        keyword: "vibe check",
+        pattern: "vibe check <lhs> not twinning <rhs>",
+        operands: "lhs and rhs are any operands",
+        semantics: "runs the next line only if lhs != rhs, otherwise skips it",
+        extra_cost: 0,
+        running_cost: "",
+        example: "vibe check i not twinning n",
+    },
+    InstructionDoc {
+        keyword: "simp for",
+        pattern: "simp for <line>",
+        operands: "line is any operand, counted from 1",
+        semantics: "jumps to the given line, which can be the value of a variable",
+        extra_cost: 0,
+        running_cost: "",
+        example: "simp for 1",
+    },
+    InstructionDoc {
+        keyword: "brb",
+        pattern: "brb <line>",
+        operands: "line is any operand, counted from 1",
+        semantics: "jumps to the given line, and comes back to the next one at `im back`",
+        extra_cost: 0,
+        running_cost: "",
+        example: "brb 1",
+    },
+    InstructionDoc {
+        keyword: "im back",
+        pattern: "im back",
+        operands: "none",
+        semantics: "goes back to the line after the last `brb` that hasn't come back yet",
+        extra_cost: 0,
+        running_cost: "",
+        example: "im back",
+    },
+    InstructionDoc {
+        keyword: "go touch some grass",
+        pattern: "go touch some grass",
+        operands: "none",
+        semantics: "ends the program",
+        extra_cost: 0,
+        running_cost: "",
+        example: "go touch some grass",
+    },
+    InstructionDoc {
+        keyword: "no cap",
+        pattern: "no cap <src>",
+        operands: "src is any operand",
+        semantics: "fails with a runtime error if src is 0, skipped when judging",
+        extra_cost: 0,
+        running_cost: "",
+        example: "no cap n",
+    },
+    InstructionDoc {
+        keyword: "rage quit",
+        pattern: "rage quit <src>",
+        operands: "src is any operand, usually a \"string literal\"",
+        semantics: "stops the program with a runtime error that says src, even when \
+            judging, where only string literals are shown",
+        extra_cost: 0,
+        running_cost: "",
+        example: "rage quit \"n is negative\"",
+    },
+];
+
+impl InstructionDoc {
+    // every entry whose keyword the line starts with, or that starts with
+    // the line, so both `explain vibe` and `explain vibe check a ratios b` work
+    fn lookup(line: &str) -> Vec<&'static InstructionDoc> {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        INSTRUCTION_DOCS
+            .iter()
+            .filter(|doc| {
+                let keyword = doc.keyword.split_whitespace();
+                !tokens.is_empty() && keyword.zip(&tokens).all(|(a, b)| a == *b)
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for InstructionDoc {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{}", self.pattern)?;
+        writeln!(f, "  operands: {}", self.operands)?;
+        writeln!(f, "  does: {}", self.semantics)?;
+        write!(
+            f,
+            "  cost: length of the line + {}",
+            Program::INSTRUCTION_BASE_COST + self.extra_cost
+        )?;
+        if !self.running_cost.is_empty() {
+            write!(f, " + {}", self.running_cost)?;
+        }
+        writeln!(f)?;
+        write!(f, "  example: {}", self.example)
+    }
+}
+
+impl ProgramMetadata {
+    const LANGUAGE_VERSION: u32 = 2;
+    const FEATURES: &'static [&'static str] = &["lowkey-yeet", "no-cap"];
+    fn add_directive(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "author" => self.author = Some(value.to_string()),
+            "task" => {
+                let task = value
+                    .parse()
+                    .map_err(|_| format!("cannot parse task id '{}'", compress(value)))?;
+                self.task = Some(task);
+            }
+            "version" => {
+                let version = value
+                    .parse()
+                    .map_err(|_| format!("cannot parse version '{}'", compress(value)))?;
+                if version > Self::LANGUAGE_VERSION {
+                    return Err(format!("language version {} is from the future", version));
+                }
+                self.version = Some(version);
+            }
+            "needs" => {
+                for feature in value.split_whitespace() {
+                    if !Self::FEATURES.contains(&feature) {
+                        return Err(format!("never heard of feature '{}'", compress(feature)));
+                    }
+                    self.features.push(feature.to_string());
+                }
+            }
+            _ => return Err(format!("unknown directive '{}'", compress(key))),
+        }
+        Ok(())
+    }
+}
+
+impl Program {
+    const INSTRUCTION_BASE_COST: usize = 5;
+    // what `sort it out` charges per comparison a merge sort would make
+    const SORT_COST: usize = 1;
+    fn line_cost(line: &str) -> usize {
+        line.len() + Self::INSTRUCTION_BASE_COST
+    }
+    fn compile(lines: &[String]) -> Result<Program, Verdict> {
+        Self::compile_with(lines, Self::line_cost, &[])
+    }
+    fn compile_with(
+        lines: &[String],
+        cost_model: CostModel,
+        opcodes: &[(OpcodeId, Arc<Opcode>)],
+    ) -> Result<Program, Verdict> {
+        let mut code = CompiledProgram {
+            opcodes: opcodes.to_vec(),
+            ..CompiledProgram::default()
+        };
+        let mut in_header = true;
+        for (lineno, line) in lines.iter().enumerate() {
+            if line.to_lowercase().find("based").is_some() {
+                return Err(Verdict::Based());
+            }
+            let line = strip_comment(line);
+            // `fr fr <key> <value>` directives in the header are nops that
+            // describe the program
+            if let ["fr", "fr", key, ref value @ ..] =
+                line.split_whitespace().collect::<Vec<_>>()[..]
+            {
+                if !in_header {
+                    return Err(Verdict::CompileError(
+                        lineno,
+                        "`fr fr` directives only go at the top".to_string(),
+                    ));
+                }
+                if let Err(message) = code.metadata.add_directive(key, &value.join(" ")) {
+                    return Err(Verdict::CompileError(lineno, message));
+                }
+                code.instructions.push(Instruction::Nop());
+                code.costs.push(cost_model(line));
+                continue;
+            }
+            match Self::compile_line(line, cost_model, opcodes) {
+                Ok((instruction, cost)) => {
+                    in_header &= matches!(instruction, Instruction::Nop());
+                    code.instructions.push(instruction);
+                    code.costs.push(cost);
+                }
+                Err(message) => {
+                    return Err(Verdict::CompileError(lineno, message));
+                }
+            }
+        }
+        // from version 2 on blank lines and comments aren't instructions, so
+        // spacing code out doesn't move the targets of `simp for`
+        if code.metadata.version.is_some_and(|version| version >= 2) {
+            let kept = (0..lines.len())
+                .filter(|&lineno| !strip_comment(&lines[lineno]).trim().is_empty())
+                .collect::<Vec<_>>();
+            code.instructions = kept.iter().map(|&pc| code.instructions[pc]).collect();
+            code.costs = kept.iter().map(|&pc| code.costs[pc]).collect();
+            code.source_lines = kept;
+            code.source_len = lines.len();
+        }
+        code.lower().map_err(|verdict| code.remap(verdict))?;
+        Ok(Self::spawn(Arc::new(code)))
+    }
+    // one instruction and what it costs to run
+    fn compile_line(
+        line: &str,
+        cost_model: CostModel,
+        opcodes: &[(OpcodeId, Arc<Opcode>)],
+    ) -> Result<(Instruction, usize), String> {
+        let tokens = tokenize(line)?;
+        let instruction = Instruction::try_from(line).or_else(|message| {
+            opcodes
+                .iter()
+                .find_map(|(id, opcode)| opcode.parse(*id, &tokens))
+                .unwrap_or(Err(message))
+        })?;
+        // operators and custom opcodes cost extra on top of the line
+        let extra_cost = match &instruction {
+            Instruction::Evaluate(..)
+            | Instruction::Mul(..)
+            | Instruction::Div(..)
+            | Instruction::Mod(..) => Self::INSTRUCTION_BASE_COST,
+            Instruction::Custom(id, _) => opcodes
+                .iter()
+                .find(|(known, _)| known == id)
+                .map_or(0, |(_, opcode)| opcode.cost),
+            _ => 0,
+        };
+        Ok((instruction, cost_model(line) + extra_cost))
+    }
+    // a fresh execution state for an already compiled program
+    fn spawn(code: Arc<CompiledProgram>) -> Program {
+        Program {
+            code,
+            time_limit: usize::MAX,
+            ..Program::default()
+        }
+    }
+    // the lines that can run after `pc`, which may include lines past the end
+    fn successors(&self, pc: usize) -> Vec<usize> {
+        let instruction = &self.code.instructions[pc];
+        // computed jumps could go to any line
+        let target = || -> Vec<usize> {
+            match instruction.constant_target() {
+                Some(line) => line
+                    .checked_sub(1)
+                    .and_then(|line| line.try_into().ok())
+                    .into_iter()
+                    .collect(),
+                None => (0..self.code.instructions.len()).collect(),
+            }
+        };
+        match instruction {
+            Instruction::Jump(_) => target(),
+            // the line after a call runs once the subroutine is back, which
+            // is where `im back` goes
+            Instruction::Call(_) => {
+                let mut successors = target();
+                successors.push(pc + 1);
+                successors
+            }
+            Instruction::Return() | Instruction::ReturnSub() | Instruction::Abort(_) => vec![],
+            instruction if instruction.is_vibe_check() => vec![pc + 1, pc + 2],
+            _ => vec![pc + 1],
+        }
+    }
+    fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.code.instructions.len()];
+        let mut stack = vec![0];
+        while let Some(pc) = stack.pop() {
+            if pc < reachable.len() && !reachable[pc] {
+                reachable[pc] = true;
+                stack.extend(self.successors(pc));
+            }
+        }
+        reachable
+    }
+    // the first `yeet` if there are some, but none of them can run before the
+    // program returns, so it can only ever get "print something". computed
+    // jumps could go anywhere, so programs with them are given a pass
+    fn unreachable_result(&self) -> Option<usize> {
+        let reachable = self.reachable();
+        let instructions = &self.code.instructions;
+        let reached = |pc: &usize| reachable[*pc];
+        let mut outputs = (0..instructions.len()).filter(|&pc| {
+            matches!(
+                instructions[pc],
+                Instruction::Output(_) | Instruction::OutputChar(_)
+            )
+        });
+        let computed_jump = (0..instructions.len()).filter(reached).any(|pc| {
+            matches!(
+                instructions[pc],
+                Instruction::Jump(_) | Instruction::Call(_)
+            ) && instructions[pc].constant_target().is_none()
+        });
+        match outputs.clone().any(|pc| reached(&pc)) || computed_jump {
+            true => None,
+            false => outputs.next(),
+        }
+    }
+    // warns about an unreachable result, or makes it a compile error
+    fn check_result(self, strict: bool) -> Result<Program, Verdict> {
+        let Some(pc) = self.unreachable_result() else {
+            return Ok(self);
+        };
+        let message = "no `yeet` can run before `go touch some grass`, so ya code can only ever get \"print something\"".to_string();
+        let line = self.code.source_line(pc);
+        if strict {
+            return Err(Verdict::CompileError(line, message));
+        }
+        eprintln!("warning: line {}: {}", line + 1, message);
+        Ok(self)
+    }
+    // tasks can ban instructions that would make them trivial, which are
+    // compile errors there and nowhere else
+    fn check_banned(self, task: i32) -> Result<Program, Verdict> {
+        let banned = banned_instructions(task);
+        for (pc, instruction) in self.code.instructions.iter().enumerate() {
+            let line = instruction.to_string();
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            if let Some(keyword) = banned
+                .iter()
+                .find(|keyword| tokens.starts_with(&keyword.split_whitespace().collect::<Vec<_>>()))
+            {
+                return Err(Verdict::CompileError(
+                    self.code.source_line(pc),
+                    format!("`{}` is banned in task {}, build it urself", keyword, task),
+                ));
+            }
+        }
+        Ok(self)
+    }
+    // constant indices that are out of bounds for every valid input
+    fn bounds_lints(&self, format: &InputFormat) -> Vec<String> {
+        let mut lints = Vec::new();
+        for (pc, instruction) in self.code.instructions.iter().enumerate() {
+            for operand in instruction.operands() {
+                let Operand::ArrayConstIndex(array, index) = operand else {
+                    continue;
+                };
+                match format.max_len(array.name()) {
+                    _ if *index < 0 => lints.push(format!(
+                        "line {}: {}[{}] is always out of bounds, indices can't be negative",
+                        self.code.source_line(pc) + 1,
+                        array,
+                        index
+                    )),
+                    Some(max_len) if *index >= max_len as i64 => lints.push(format!(
+                        "line {}: {}[{}] is always out of bounds, {} has at most {} elements",
+                        self.code.source_line(pc) + 1,
+                        array,
+                        index,
+                        array,
+                        max_len
+                    )),
+                    _ => {}
+                }
+            }
+        }
+        lints
+    }
+    // things that compile but are almost certainly mistakes
+    fn lints(&self) -> Vec<String> {
+        let len = self.code.instructions.len();
+        let reachable = self.reachable();
+        let mut lints = Vec::new();
+        for (pc, instruction) in self.code.instructions.iter().enumerate() {
+            let line = self.code.source_line(pc) + 1;
+            match instruction {
+                Instruction::Input(operand) | Instruction::InputBulk(operand)
+                    if !matches!(operand, Operand::Variable(_)) =>
+                {
+                    lints.push(format!(
+                        "line {}: input operand must be an identifier",
+                        line
+                    ));
+                }
+                _ => {}
+            }
+            if !reachable[pc] && !matches!(instruction, Instruction::Nop()) {
+                lints.push(format!("line {}: unreachable", line));
+            }
+            if reachable[pc] && self.successors(pc).contains(&len) {
+                lints.push(format!(
+                    "line {}: ya code can run off the end from here",
+                    line
+                ));
+            }
+        }
+        if !self
+            .code
+            .instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Return()))
+        {
+            lints.push("nothing ever says `go touch some grass`".to_string());
+        }
+        if !self.code.instructions.iter().any(|instruction| {
+            matches!(
+                instruction,
+                Instruction::Output(_) | Instruction::OutputChar(_)
+            )
+        }) {
+            lints.push("nothing ever gets yeeted".to_string());
+        }
+        if let Some(pc) = self.unreachable_result() {
+            lints.push(format!(
+                "line {}: no `yeet` can run before `go touch some grass`",
+                self.code.source_line(pc) + 1
+            ));
+        }
+        lints
+    }
+    // the program with identifiers renamed in order of first appearance and
+    // nops dropped (except right after a compare, where they matter), so that
+    // renaming variables or adding spacing doesn't change anything
+    fn canonicalize(&self) -> Vec<String> {
+        let len = self.code.instructions.len();
+        let kept = (0..len)
+            .map(|pc| {
+                !matches!(self.code.instructions[pc], Instruction::Nop())
+                    || pc > 0 && self.code.instructions[pc - 1].is_vibe_check()
+            })
+            .collect::<Vec<_>>();
+        let mut new_pc = vec![0];
+        for &kept in &kept {
+            new_pc.push(new_pc.last().unwrap() + kept as i64);
+        }
+        let mut names = HashMap::new();
+        let mut rename = |name: &str| {
+            let next = names.len();
+            names
+                .entry(name.to_string())
+                .or_insert_with(|| format!("v{}", next))
+                .clone()
+        };
+        self.code
+            .instructions
+            .iter()
+            .zip(&kept)
+            .filter(|(_, &kept)| kept)
+            .map(|(instruction, _)| match instruction {
+                instruction if instruction.constant_target() >= Some(1) => {
+                    let pc = (instruction.constant_target().unwrap() - 1) as usize;
+                    let target = match new_pc.get(pc) {
+                        Some(&target) => target,
+                        None => new_pc[len] + (pc - len) as i64,
+                    };
+                    instruction.retarget(target + 1).to_string()
+                }
+                instruction => instruction
+                    .map_operands(|operand| operand.map_identifiers(&mut rename))
+                    .to_string(),
+            })
+            .collect()
+    }
+    // hashes of every k consecutive canonical instructions
+    fn fingerprint(&self) -> BTreeSet<u64> {
+        const K: usize = 4;
+        let lines = self.canonicalize();
+        lines
+            .windows(K.min(lines.len()).max(1))
+            .map(|shingle| {
+                let mut hasher = DefaultHasher::new();
+                shingle.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect()
+    }
+    // fnv-1a over the canonical source, which unlike DefaultHasher stays the
+    // same across rust versions, so it can be stored
+    fn semantic_hash(&self) -> u64 {
+        fnv1a(
+            self.canonicalize()
+                .iter()
+                .flat_map(|line| line.bytes().chain([b'\n'])),
+        )
+    }
+    fn metadata(&self) -> &ProgramMetadata {
+        &self.code.metadata
+    }
+    // `no cap` is meant for jury solutions while stress testing, so in contest
+    // mode it is compiled out into a nop that costs nothing
+    fn strip_for_judging(&mut self) {
+        self.hide_test_data();
+        let code = Arc::make_mut(&mut self.code);
+        for (instruction, cost) in code.instructions.iter_mut().zip(&mut code.costs) {
+            if let Instruction::Assert(_) = instruction {
+                *instruction = Instruction::Nop();
+                *cost = 0;
+            }
+        }
+    }
+    // `rage quit` only keeps its string literals, as a variable's value could
+    // leak the test data into the verdict, and so could the line a jump goes
+    // to. `score` runs the judge's tests too, so it hides them as well
+    fn hide_test_data(&mut self) {
+        let code = Arc::make_mut(&mut self.code);
+        code.judging = true;
+        for instruction in &mut code.instructions {
+            if let Instruction::Abort(src) = instruction {
+                if !matches!(src, Operand::Text(_)) {
+                    *src = Operand::Text(Symbol::intern("(value hidden when judging)"));
+                }
+            }
+        }
+    }
+    // read-only views for whoever wants to look at a program without
+    // messing up its execution
+    fn variables(&self) -> impl Iterator<Item = (Symbol, &Variable)> {
+        self.variables
+            .iter()
+            .enumerate()
+            .filter_map(|(index, var)| Some((Symbol(index as u32), var.as_ref()?)))
+    }
+    fn pc(&self) -> usize {
+        self.pc
+    }
+    fn runtime(&self) -> usize {
+        self.runtime
+    }
+    fn instruction_at(&self, pc: usize) -> Option<&Instruction> {
+        self.code.instructions.get(pc)
+    }
+    fn variable(&self, var: Symbol) -> Option<&Variable> {
+        self.variables.get(var.index()).and_then(Option::as_ref)
+    }
+    fn variable_slot_mut(&mut self, var: Symbol) -> &mut Option<Variable> {
+        let variables = Rc::make_mut(&mut self.variables);
+        if variables.len() <= var.index() {
+            variables.resize(var.index() + 1, None);
+        }
+        &mut variables[var.index()]
+    }
+    fn get_int(&self, var: Symbol) -> Result<i64, Verdict> {
+        match self.variable(var) {
+            Some(Variable::Integer(value)) => Ok(*value),
+            Some(variable) => Err(Verdict::RuntimeError(
+                self.pc,
+                format!(
+                    "expected integer, found {} {}",
+                    variable.kind(),
+                    compress(var.name())
+                ),
+            )),
+            None => Err(Verdict::RuntimeError(
+                self.pc,
+                format!("no such variable {}", compress(var.name())),
+            )),
+        }
+    }
+    fn get_int_mut_or_default(&mut self, var: Symbol) -> Result<&mut i64, Verdict> {
+        let pc = self.pc;
+        let memory = &mut self.memory;
+        let slot = Rc::make_mut(&mut self.variables);
+        if slot.len() <= var.index() {
+            slot.resize(var.index() + 1, None);
+        }
+        let slot = slot[var.index()].get_or_insert_with(|| {
+            let variable = Variable::Integer(0);
+            memory.allocate(MemoryUsage::footprint(var.name(), &variable));
+            variable
+        });
+        match slot {
+            Variable::Integer(value) => Ok(value),
+            variable => Err(Verdict::RuntimeError(
+                pc,
+                format!(
+                    "expected integer, found {} {}",
+                    variable.kind(),
+                    compress(var.name())
+                ),
+            )),
+        }
+    }
+    fn get_arr(&self, var: Symbol) -> Result<&[i64], Verdict> {
+        match self.variable(var) {
+            Some(Variable::Array(value)) => Ok(value),
+            Some(variable) => Err(Verdict::RuntimeError(
+                self.pc,
+                format!(
+                    "expected array, found {} {}",
+                    variable.kind(),
+                    compress(var.name())
+                ),
+            )),
+            None => Err(Verdict::RuntimeError(
+                self.pc,
+                format!("no such variable {}", compress(var.name())),
+            )),
+        }
+    }
+    fn get_arr_mut(&mut self, var: Symbol) -> Result<&mut [i64], Verdict> {
+        // only copies the variables if a snapshot still shares them
+        self.get_arr(var)?;
+        match self.variable_slot_mut(var) {
+            Some(Variable::Array(value)) => Ok(value),
+            _ => unreachable!(),
+        }
+    }
+    fn get_grid(&self, var: Symbol) -> Result<&[Vec<i64>], Verdict> {
+        match self.variable(var) {
+            Some(Variable::Grid(rows)) => Ok(rows),
+            Some(variable) => Err(Verdict::RuntimeError(
+                self.pc,
+                format!(
+                    "expected grid, found {} {}",
+                    variable.kind(),
+                    compress(var.name())
+                ),
+            )),
+            None => Err(Verdict::RuntimeError(
+                self.pc,
+                format!("no such variable {}", compress(var.name())),
+            )),
+        }
+    }
+    fn get_grid_mut(&mut self, var: Symbol) -> Result<&mut [Vec<i64>], Verdict> {
+        self.get_grid(var)?;
+        match self.variable_slot_mut(var) {
+            Some(Variable::Grid(rows)) => Ok(rows),
+            _ => unreachable!(),
+        }
+    }
+    fn get_index(&self, index: &Index) -> Result<usize, Verdict> {
+        match index {
+            Index::Constant(index) => self.checked_index(*index),
+            Index::Variable(var) => self.checked_index(self.get_int(*var)?),
+            Index::Nested(index) => self.checked_index(self.get_value(&index.get())?),
+        }
+    }
+    fn checked_index(&self, index: i64) -> Result<usize, Verdict> {
+        usize::try_from(index).map_err(|_| {
+            Verdict::RuntimeError(
+                self.pc,
+                format!("index {} is negative, arrays start at 0 bestie", index),
+            )
+        })
+    }
+    // the values of `start`'s array from `start` up to, but not including, index `end`
+    fn get_range(&self, keyword: &str, start: &Operand, end: &Operand) -> Result<&[i64], Verdict> {
+        let (array, from) = match start {
+            Operand::ArrayConstIndex(array, index) => (array, *index),
+            Operand::ArrayVarIndex(array, index) => (array, self.get_int(*index)?),
+            Operand::ArrayNestedIndex(array, index) => (array, self.get_value(&index.get())?),
+            _ => unreachable!(),
+        };
+        let to = self.get_value(end)?;
+        let values = self.get_arr(*array)?;
+        if from < 0 || to < from || to > values.len() as i64 {
+            return Err(Verdict::RuntimeError(
+                self.pc,
+                format!(
+                    "can't {} {}[{}..{}], it has {} elements",
+                    keyword,
+                    compress(array.name()),
+                    from,
+                    to,
+                    values.len()
+                ),
+            ));
+        }
+        Ok(&values[from as usize..to as usize])
+    }
+    fn get_value(&self, operand: &Operand) -> Result<i64, Verdict> {
+        match operand {
+            Operand::Constant(value) => Ok(*value),
+            Operand::Text(text) => Err(Verdict::RuntimeError(
+                self.pc,
+                format!("\"{}\" is text, not a number", compress(text.name())),
+            )),
+            Operand::Variable(var) => self.get_int(*var),
+            Operand::ArrayConstIndex(array, index) => {
+                let index = self.checked_index(*index)?;
+                let array = self.get_arr(*array)?;
+                array.get(index).copied().ok_or_else(|| {
+                    Verdict::RuntimeError(self.pc, format!("index {} out of bounds", index))
                 })
             }
             Operand::ArrayVarIndex(array, index) => {
-                let lineno = self.pc;
-                let index = self.get_int_mut(index).copied()? as usize;
-                let array = self.get_arr_mut(array)?;
+                let index = self.checked_index(self.get_int(*index)?)?;
+                let array = self.get_arr(*array)?;
                 array.get(index).copied().ok_or_else(|| {
-                    Verdict::RuntimeError(lineno, format!("index {} out of bounds", index))
+                    Verdict::RuntimeError(self.pc, format!("index {} out of bounds", index))
+                })
+            }
+            Operand::ArrayNestedIndex(array, index) => {
+                let index = self.checked_index(self.get_value(&index.get())?)?;
+                let array = self.get_arr(*array)?;
+                array.get(index).copied().ok_or_else(|| {
+                    Verdict::RuntimeError(self.pc, format!("index {} out of bounds", index))
                 })
             }
+            Operand::GridIndex(grid, row, col) => {
+                let (row, col) = (self.get_index(row)?, self.get_index(col)?);
+                let grid = self.get_grid(*grid)?;
+                grid.get(row)
+                    .and_then(|values| values.get(col))
+                    .copied()
+                    .ok_or_else(|| {
+                        Verdict::RuntimeError(
+                            self.pc,
+                            format!("index [{}][{}] out of bounds", row, col),
+                        )
+                    })
+            }
         }
     }
     fn get_reference_mut(&mut self, operand: &Operand) -> Result<&mut i64, Verdict> {
@@ -235,342 +2589,5735 @@ impl Program {
                 self.pc,
                 format!("integer constant {} is not &mut i64", value),
             )),
-            Operand::Variable(var) => self.get_int_mut_or_default(var),
+            Operand::Text(text) => Err(Verdict::RuntimeError(
+                self.pc,
+                format!(
+                    "string literal \"{}\" is not &mut i64",
+                    compress(text.name())
+                ),
+            )),
+            Operand::Variable(var) => self.get_int_mut_or_default(*var),
             Operand::ArrayConstIndex(array, index) => {
                 let lineno = self.pc;
-                let array = self.get_arr_mut(array)?;
-                array.get_mut(*index).ok_or_else(|| {
+                let index = self.checked_index(*index)?;
+                let array = self.get_arr_mut(*array)?;
+                array.get_mut(index).ok_or_else(|| {
                     Verdict::RuntimeError(lineno, format!("index {} out of bounds", index))
                 })
             }
             Operand::ArrayVarIndex(array, index) => {
                 let lineno = self.pc;
-                let index = self.get_int_mut(index).copied()? as usize;
-                let array = self.get_arr_mut(array)?;
+                let index = self.checked_index(self.get_int(*index)?)?;
+                let array = self.get_arr_mut(*array)?;
+                array.get_mut(index).ok_or_else(|| {
+                    Verdict::RuntimeError(lineno, format!("index {} out of bounds", index))
+                })
+            }
+            Operand::ArrayNestedIndex(array, index) => {
+                let lineno = self.pc;
+                let index = self.checked_index(self.get_value(&index.get())?)?;
+                let array = self.get_arr_mut(*array)?;
                 array.get_mut(index).ok_or_else(|| {
                     Verdict::RuntimeError(lineno, format!("index {} out of bounds", index))
                 })
             }
+            Operand::GridIndex(grid, row, col) => {
+                let lineno = self.pc;
+                let (row, col) = (self.get_index(row)?, self.get_index(col)?);
+                let grid = self.get_grid_mut(*grid)?;
+                grid.get_mut(row)
+                    .and_then(|values| values.get_mut(col))
+                    .ok_or_else(|| {
+                        Verdict::RuntimeError(
+                            lineno,
+                            format!("index [{}][{}] out of bounds", row, col),
+                        )
+                    })
+            }
+        }
+    }
+    // deep enough for any honest recursion over the tasks' inputs
+    const MAX_CALL_DEPTH: usize = 10000;
+    fn feed_inputs(&mut self, variables: impl IntoIterator<Item = Variable>) {
+        self.input.values.extend(variables)
+    }
+    // everything printed that nobody has read yet
+    fn drain_outputs(&mut self) -> Vec<Variable> {
+        let outputs = self.peek_outputs().to_vec();
+        self.output_position = self.output.len();
+        outputs
+    }
+    fn peek_outputs(&self) -> &[Variable] {
+        &self.output[self.output_position..]
+    }
+    fn flush_spat(&mut self) {
+        if self.spat.len() > self.spat_flushed {
+            let text = self.spat[self.spat_flushed..].to_string();
+            self.output.push(Variable::Text(text));
+            self.spat_flushed = self.spat.len();
+        }
+    }
+    // cheap enough to take before every step: the variables are shared with
+    // the program until one of them is written to, and the queues are only
+    // remembered by their positions
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            variables: Rc::clone(&self.variables),
+            memory: self.memory,
+            input: (
+                self.input.values.len(),
+                self.input.position,
+                self.input.stream_position,
+            ),
+            output_len: self.output.len(),
+            output_position: self.output_position,
+            debug_output_len: self.debug_output.len(),
+            spat_len: self.spat.len(),
+            spat_flushed: self.spat_flushed,
+            runtime: self.runtime,
+            pc: self.pc,
+            call_stack: self.call_stack.clone(),
+            returned: self.returned,
+            read_input: self.read_input,
+            printed_before_input: self.printed_before_input,
+            rng: self.rng,
         }
     }
-    fn add_input(&mut self, variable: Variable) {
-        self.input.push_back(variable)
+    fn restore(&mut self, snapshot: &Snapshot) {
+        self.variables = Rc::clone(&snapshot.variables);
+        self.memory = snapshot.memory;
+        let (len, position, stream_position) = snapshot.input;
+        self.input.values.truncate(len);
+        self.input.position = position;
+        self.input.stream_position = stream_position;
+        self.output.truncate(snapshot.output_len);
+        self.output_position = snapshot.output_position;
+        self.debug_output.truncate(snapshot.debug_output_len);
+        self.spat.truncate(snapshot.spat_len);
+        self.spat_flushed = snapshot.spat_flushed;
+        self.runtime = snapshot.runtime;
+        self.pc = snapshot.pc;
+        self.call_stack.clone_from(&snapshot.call_stack);
+        self.returned = snapshot.returned;
+        self.read_input = snapshot.read_input;
+        self.printed_before_input = snapshot.printed_before_input;
+        self.rng = snapshot.rng;
+    }
+    // the pc a `simp for` or `brb` goes to
+    fn jump_target(&self, cur_pc: usize, target: &Operand) -> Result<usize, Verdict> {
+        let len = self.code.instructions.len();
+        match target {
+            // constant targets are in range, lowering made sure of that
+            Operand::Constant(line) => Ok(*line as usize - 1),
+            target => {
+                let line = self.get_value(target)?;
+                if line < 1 || line > len as i64 {
+                    let line = match self.code.judging {
+                        true => "(value hidden when judging)".to_string(),
+                        false => line.to_string(),
+                    };
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        format!("line {} goes nowhere, there are only {} lines", line, len),
+                    ));
+                }
+                Ok(line as usize - 1)
+            }
+        }
+    }
+    // runs the line at pc
+    fn execute_one(&mut self) -> Result<(), Verdict> {
+        self.step().map_err(|verdict| self.code.remap(verdict))
+    }
+    fn step(&mut self) -> Result<(), Verdict> {
+        let cur_pc = self.pc;
+        let mut next_pc = cur_pc + 1;
+        // holding on to the code separately lets instructions be borrowed
+        // instead of cloned on every step
+        let code = Arc::clone(&self.code);
+        let Some(instruction) = code.instructions.get(cur_pc) else {
+            return Err(Verdict::RuntimeError(
+                cur_pc,
+                format!("that's not even a line"),
+            ));
+        };
+        self.runtime = self.runtime.saturating_add(self.code.costs[cur_pc]);
+        match instruction {
+            Instruction::Nop() => {}
+            // in bulk, a count and then that many integers become one array,
+            // costing one more per integer on top of the line
+            Instruction::Input(dst) | Instruction::InputBulk(dst) => {
+                let Operand::Variable(var) = dst else {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        format!("input operand must be an identifier"),
+                    ));
+                };
+                let channel = &mut self.input;
+                if channel.stream_position > 0 {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        "you can't yoink from input that was already nibbled".to_string(),
+                    ));
+                }
+                let Some(input) = channel.values.get(channel.position).cloned() else {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        format!("you're reading from nothing"),
+                    ));
+                };
+                channel.position += 1;
+                let input = match (instruction, input) {
+                    (Instruction::InputBulk(..), Variable::Integer(count)) => {
+                        let start = channel.position;
+                        let end = usize::try_from(count)
+                            .ok()
+                            .and_then(|count| start.checked_add(count))
+                            .filter(|end| *end <= channel.values.len())
+                            .ok_or_else(|| {
+                                Verdict::RuntimeError(
+                                    cur_pc,
+                                    format!(
+                                        "can't yoink {} values in bulk, there aren't that many",
+                                        count
+                                    ),
+                                )
+                            })?;
+                        self.metered(|_, meter| meter.tick(end - start))?;
+                        let values = self.input.values[start..end]
+                            .iter()
+                            .map(|value| match value {
+                                Variable::Integer(value) => Ok(*value),
+                                value => Err(Verdict::RuntimeError(
+                                    cur_pc,
+                                    format!(
+                                        "yoinking in bulk takes integers, found {}",
+                                        value.kind()
+                                    ),
+                                )),
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+                        self.input.position = end;
+                        Variable::Array(values)
+                    }
+                    (Instruction::InputBulk(..), input) => {
+                        return Err(Verdict::RuntimeError(
+                            cur_pc,
+                            format!(
+                                "yoinking in bulk starts with a count, found {}",
+                                input.kind()
+                            ),
+                        ));
+                    }
+                    (_, input) => input,
+                };
+                self.memory
+                    .allocate(MemoryUsage::footprint(var.name(), &input));
+                if let Some(old) = self.variable_slot_mut(*var).replace(input) {
+                    self.memory.free(MemoryUsage::footprint(var.name(), &old));
+                }
+                self.read_input = true;
+            }
+            // the end of the input reads as -1
+            Instruction::InputChar(dst) => {
+                let channel = &mut self.input;
+                if channel.position > 0 {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        "you can't nibble from input that was already yoinked".to_string(),
+                    ));
+                }
+                if channel.stream_values != channel.values.len() {
+                    channel.stream = format!("{}\n", input_text(&channel.values))
+                        .chars()
+                        .collect();
+                    channel.stream_values = channel.values.len();
+                }
+                let value = match channel.stream.get(channel.stream_position) {
+                    Some(&c) => {
+                        channel.stream_position += 1;
+                        c as i64
+                    }
+                    None => -1,
+                };
+                *self.get_reference_mut(dst)? = value;
+                self.read_input = true;
+            }
+            Instruction::OutputChar(src) => {
+                self.printed_before_input |= !self.read_input;
+                let value = self.get_value(src)?;
+                let Some(c) = u32::try_from(value).ok().and_then(char::from_u32) else {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        format!("{} isn't a character", value),
+                    ));
+                };
+                self.spat.push(c);
+            }
+            Instruction::Output(src) => {
+                self.printed_before_input |= !self.read_input;
+                self.flush_spat();
+                if let Operand::Variable(var) = src {
+                    let Some(value) = self.variable(*var) else {
+                        return Err(Verdict::RuntimeError(
+                            cur_pc,
+                            format!("you're printing nothing"),
+                        ));
+                    };
+                    self.output.push(value.clone());
+                } else if let Operand::Text(text) = src {
+                    self.output.push(Variable::Text(text.name().to_string()));
+                } else {
+                    let output = self.get_value(src)?;
+                    self.output.push(Variable::Integer(output));
+                }
+            }
+            // goes to a separate channel that the checker never looks at
+            Instruction::DebugOutput(src) => {
+                let value = match src {
+                    Operand::Variable(var) => match self.variable(*var) {
+                        Some(value) => value.clone(),
+                        None => {
+                            return Err(Verdict::RuntimeError(
+                                cur_pc,
+                                "you're printing nothing".to_string(),
+                            ))
+                        }
+                    },
+                    Operand::Text(text) => Variable::Text(text.name().to_string()),
+                    _ => Variable::Integer(self.get_value(src)?),
+                };
+                self.debug_output.push((cur_pc, value));
+            }
+            Instruction::Assign(dst, src) => {
+                *self.get_reference_mut(dst)? = self.get_value(src)?;
+            }
+            // src is read either way, like a cmov
+            Instruction::CondAssign(dst, src, cond) => {
+                let value = self.get_value(src)?;
+                if self.get_value(cond)? != 0 {
+                    *self.get_reference_mut(dst)? = value;
+                }
+            }
+            Instruction::Random(dst, bound) => {
+                let bound = self.get_value(bound)?;
+                if bound < 1 {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        format!("can't gamble up to {}, there's nothing to roll", bound),
+                    ));
+                }
+                // scaling instead of taking a remainder keeps small numbers
+                // from coming up more often
+                let value = ((self.rng.next() as u128 * bound as u128) >> 64) as i64;
+                *self.get_reference_mut(dst)? = value;
+            }
+            Instruction::Max(dst, a, b) => {
+                let value = self.get_value(a)?.max(self.get_value(b)?);
+                *self.get_reference_mut(dst)? = value;
+            }
+            Instruction::Min(dst, a, b) => {
+                let value = self.get_value(a)?.min(self.get_value(b)?);
+                *self.get_reference_mut(dst)? = value;
+            }
+            Instruction::Swap(a, b) => {
+                let (x, y) = (self.get_value(a)?, self.get_value(b)?);
+                *self.get_reference_mut(a)? = y;
+                *self.get_reference_mut(b)? = x;
+            }
+            Instruction::Evaluate(dst, lhs, op, rhs) => {
+                let value = op.apply(self.get_value(lhs)?, self.get_value(rhs)?);
+                *self.get_reference_mut(dst)? = value;
+            }
+            Instruction::Add(dst, src) => {
+                *self.get_reference_mut(dst)? += self.get_value(src)?;
+            }
+            Instruction::Sub(dst, src) => {
+                *self.get_reference_mut(dst)? -= self.get_value(src)?;
+            }
+            Instruction::Mul(dst, src) => {
+                let factor = self.get_value(src)?;
+                let dst = self.get_reference_mut(dst)?;
+                *dst = dst.wrapping_mul(factor);
+            }
+            Instruction::Div(dst, src) | Instruction::Mod(dst, src) => {
+                let divisor = self.get_value(src)?;
+                if divisor == 0 {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        "you're dividing by zero".to_string(),
+                    ));
+                }
+                // wrapping, so i64::MIN by -1 overflows like stonks does
+                let dst = self.get_reference_mut(dst)?;
+                *dst = match instruction {
+                    Instruction::Div(..) => dst.wrapping_div(divisor),
+                    _ => dst.wrapping_rem(divisor),
+                };
+            }
+            Instruction::ShiftLeft(dst, src)
+            | Instruction::ShiftRight(dst, src)
+            | Instruction::ShiftRightUnsigned(dst, src) => {
+                let amount = self.get_value(src)?;
+                if !(0..64).contains(&amount) {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        format!("you can only slide by 0 to 63 bits, not {}", amount),
+                    ));
+                }
+                let dst = self.get_reference_mut(dst)?;
+                *dst = match instruction {
+                    Instruction::ShiftLeft(..) => *dst << amount,
+                    Instruction::ShiftRight(..) => *dst >> amount,
+                    _ => (*dst as u64 >> amount) as i64,
+                };
+            }
+            // wrapping, so i64::MIN stays itself instead of overflowing
+            Instruction::Neg(dst) => {
+                let dst = self.get_reference_mut(dst)?;
+                *dst = dst.wrapping_neg();
+            }
+            Instruction::Abs(dst) => {
+                let dst = self.get_reference_mut(dst)?;
+                *dst = dst.wrapping_abs();
+            }
+            // costs one more per value appended, on top of the line
+            Instruction::Append(dst, src) => {
+                let Operand::Variable(var) = dst else {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        "you can only hop on an array variable".to_string(),
+                    ));
+                };
+                let values = match src {
+                    Operand::Variable(src) => match self.variable(*src) {
+                        Some(Variable::Array(values)) => values.clone(),
+                        _ => vec![self.get_int(*src)?],
+                    },
+                    _ => vec![self.get_value(src)?],
+                };
+                self.metered(|_, meter| meter.tick(values.len()))?;
+                let whole_array = matches!(src, Operand::Variable(src)
+                    if matches!(self.variable(*src), Some(Variable::Array(_))));
+                if self.variable(*var).is_none() {
+                    let empty = Variable::Array(Vec::new());
+                    self.memory
+                        .allocate(MemoryUsage::footprint(var.name(), &empty));
+                    *self.variable_slot_mut(*var) = Some(empty);
+                }
+                // a grid grows by whole rows
+                if let Some(Variable::Grid(_)) = self.variable(*var) {
+                    if !whole_array {
+                        return Err(Verdict::RuntimeError(
+                            cur_pc,
+                            format!(
+                                "grid {} only takes whole rows, hop on it with an array",
+                                compress(var.name())
+                            ),
+                        ));
+                    }
+                } else {
+                    self.get_arr(*var)?;
+                }
+                self.memory.allocate(8 * values.len());
+                match self.variable_slot_mut(*var) {
+                    Some(Variable::Array(array)) => array.extend(values),
+                    Some(Variable::Grid(rows)) => rows.push(values),
+                    _ => unreachable!(),
+                }
+            }
+            // costs n·⌈log2 n⌉ comparisons, on top of the line
+            Instruction::Sort(dst) => {
+                let Operand::Variable(var) = dst else {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        "you can only sort it out on an array variable".to_string(),
+                    ));
+                };
+                let n = self.get_arr(*var)?.len();
+                let levels = n.next_power_of_two().trailing_zeros() as usize;
+                self.metered(|_, meter| {
+                    meter.tick(n.saturating_mul(levels).saturating_mul(Self::SORT_COST))
+                })?;
+                self.get_arr_mut(*var)?.sort_unstable();
+            }
+            // costs one more per value added, on top of the line
+            Instruction::Sum(dst, start, end) => {
+                fn values<'a>(
+                    program: &'a Program,
+                    start: &Operand,
+                    end: &Option<Operand>,
+                ) -> Result<&'a [i64], Verdict> {
+                    match (start, end) {
+                        (Operand::Variable(array), None) => program.get_arr(*array),
+                        (start, Some(end)) => program.get_range("stack up", start, end),
+                        _ => unreachable!(),
+                    }
+                }
+                let n = values(self, start, end)?.len();
+                self.metered(|_, meter| meter.tick(n))?;
+                // wrapping, like adding them up one by one would
+                let sum = values(self, start, end)?
+                    .iter()
+                    .fold(0i64, |sum, &value| sum.wrapping_add(value));
+                *self.get_reference_mut(dst)? = sum;
+            }
+            // costs one more per value copied, on top of the line
+            Instruction::Slice(dst, start, end) => {
+                let Operand::Variable(var) = dst else {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        "you can only copypasta into an array variable".to_string(),
+                    ));
+                };
+                let n = self.get_range("copypasta", start, end)?.len();
+                self.metered(|_, meter| meter.tick(n))?;
+                let values = self.get_range("copypasta", start, end)?.to_vec();
+                if !matches!(self.variable(*var), None | Some(Variable::Array(_))) {
+                    self.get_arr(*var)?;
+                }
+                let copy = Variable::Array(values);
+                self.memory
+                    .allocate(MemoryUsage::footprint(var.name(), &copy));
+                if let Some(old) = self.variable_slot_mut(*var).replace(copy) {
+                    self.memory.free(MemoryUsage::footprint(var.name(), &old));
+                }
+            }
+            // costs one more per character appended, on top of the line
+            Instruction::Concat(dst, src) => {
+                let Operand::Variable(var) = dst else {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        "you can only glue onto a text variable".to_string(),
+                    ));
+                };
+                let text = match src {
+                    Operand::Variable(src) => match self.variable(*src) {
+                        Some(Variable::Text(text)) => text.clone(),
+                        _ => self.get_int(*src)?.to_string(),
+                    },
+                    Operand::Text(text) => text.name().to_string(),
+                    _ => self.get_value(src)?.to_string(),
+                };
+                self.metered(|_, meter| meter.tick(text.chars().count()))?;
+                if self.variable(*var).is_none() {
+                    let empty = Variable::Text(String::new());
+                    self.memory
+                        .allocate(MemoryUsage::footprint(var.name(), &empty));
+                    *self.variable_slot_mut(*var) = Some(empty);
+                }
+                match self.variable(*var) {
+                    Some(Variable::Text(_)) => {}
+                    Some(variable) => {
+                        return Err(Verdict::RuntimeError(
+                            cur_pc,
+                            format!(
+                                "expected text, found {} {}",
+                                variable.kind(),
+                                compress(var.name())
+                            ),
+                        ))
+                    }
+                    None => unreachable!(),
+                }
+                self.memory.allocate(text.len());
+                match self.variable_slot_mut(*var) {
+                    Some(Variable::Text(dst)) => dst.push_str(&text),
+                    _ => unreachable!(),
+                }
+            }
+            Instruction::Length(dst, src) => {
+                let len = match src {
+                    Operand::Variable(var) => match self.variable(*var) {
+                        Some(Variable::Array(values)) => values.len(),
+                        Some(Variable::Text(text)) => text.chars().count(),
+                        // the number of rows
+                        Some(Variable::Grid(rows)) => rows.len(),
+                        Some(Variable::Integer(_)) => {
+                            return Err(Verdict::RuntimeError(
+                                cur_pc,
+                                format!("integer {} has no length", compress(var.name())),
+                            ))
+                        }
+                        None => {
+                            return Err(Verdict::RuntimeError(
+                                cur_pc,
+                                format!("no such variable {}", compress(var.name())),
+                            ))
+                        }
+                    },
+                    Operand::Text(text) => text.name().chars().count(),
+                    _ => {
+                        return Err(Verdict::RuntimeError(
+                            cur_pc,
+                            "only arrays and text have a length".to_string(),
+                        ))
+                    }
+                };
+                *self.get_reference_mut(dst)? = len as i64;
+            }
+            Instruction::Compare(lhs, rhs)
+            | Instruction::CompareEq(lhs, rhs)
+            | Instruction::CompareLt(lhs, rhs)
+            | Instruction::CompareLe(lhs, rhs)
+            | Instruction::CompareNe(lhs, rhs) => {
+                let lhs = self.get_value(lhs)?;
+                let rhs = self.get_value(rhs)?;
+                let passed = match instruction {
+                    Instruction::Compare(..) => lhs > rhs,
+                    Instruction::CompareEq(..) => lhs == rhs,
+                    Instruction::CompareLt(..) => lhs < rhs,
+                    Instruction::CompareLe(..) => lhs <= rhs,
+                    _ => lhs != rhs,
+                };
+                if !passed {
+                    next_pc = cur_pc + 2;
+                }
+            }
+            Instruction::Jump(target) => {
+                next_pc = self.jump_target(cur_pc, target)?;
+            }
+            Instruction::Call(target) => {
+                if self.call_stack.len() >= Self::MAX_CALL_DEPTH {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        format!("{} brbs deep and nobody came back", Self::MAX_CALL_DEPTH),
+                    ));
+                }
+                next_pc = self.jump_target(cur_pc, target)?;
+                self.call_stack.push(cur_pc + 1);
+            }
+            Instruction::ReturnSub() => match self.call_stack.pop() {
+                Some(pc) => next_pc = pc,
+                None => {
+                    return Err(Verdict::RuntimeError(
+                        cur_pc,
+                        "im back from where? nobody said brb".to_string(),
+                    ))
+                }
+            },
+            Instruction::Return() => {
+                self.flush_spat();
+                self.returned = true;
+            }
+            Instruction::Assert(src) => {
+                if self.get_value(src)? == 0 {
+                    return Err(Verdict::RuntimeError(cur_pc, format!("{} was cap", src)));
+                }
+            }
+            Instruction::Abort(src) => {
+                let message = match src {
+                    Operand::Text(text) => text.name().to_string(),
+                    Operand::Variable(var) => match self.variable(*var) {
+                        Some(value) => value.to_string(),
+                        None => self.get_int(*var)?.to_string(),
+                    },
+                    _ => self.get_value(src)?.to_string(),
+                };
+                return Err(Verdict::RuntimeError(
+                    cur_pc,
+                    format!("rage quit: {}", compress(&message)),
+                ));
+            }
+            Instruction::Custom(id, operands) => {
+                // looked up in the program instead of the registry, which
+                // would take a lock on every run of the line
+                let opcode = self
+                    .code
+                    .opcodes
+                    .iter()
+                    .find(|(known, _)| known == id)
+                    .map(|(_, opcode)| opcode.clone())
+                    .unwrap();
+                (opcode.execute)(self, operands.as_slice())?;
+            }
+        }
+        self.pc = next_pc;
+        Ok(())
+    }
+    // hints for common beginner mistakes, only shown in practice mode
+    fn hint(&self, verdict: &Verdict) -> Option<&'static str> {
+        match verdict {
+            Verdict::RuntimeError(line, _)
+                if *line == self.code.source_line(self.code.instructions.len()) =>
+            {
+                Some("ya code ran off the end, finish it with `go touch some grass`")
+            }
+            // constant targets that go nowhere don't even compile
+            Verdict::RuntimeError(_, message) if message.contains("goes nowhere") => {
+                Some("`simp for` jumped to a line that doesn't exist, lines are numbered from 1")
+            }
+            Verdict::TimeLimitExceeded()
+                if !self
+                    .code
+                    .instructions
+                    .iter()
+                    .any(|instruction| matches!(instruction, Instruction::Return())) =>
+            {
+                Some("nothing ever says `go touch some grass`, so ya code can never finish")
+            }
+            Verdict::WrongAnswer(_) if self.printed_before_input => {
+                Some("ya code printed something before reading any input")
+            }
+            _ => None,
+        }
+    }
+    fn execute(&mut self, time_limit: usize) -> Result<(), Verdict> {
+        self.execute_with(time_limit, |_| Ok(()))
+    }
+    // `after_step` gets to look at the program after every line and can
+    // stop it early
+    fn execute_with(
+        &mut self,
+        time_limit: usize,
+        mut after_step: impl FnMut(&mut Program) -> Result<(), Verdict>,
+    ) -> Result<(), Verdict> {
+        self.time_limit = time_limit;
+        loop {
+            if self.returned {
+                return Ok(());
+            }
+            if self.runtime > time_limit {
+                return Err(Verdict::TimeLimitExceeded());
+            }
+            self.execute_one()?;
+            after_step(self)?;
+        }
+    }
+}
+
+// what happened when `eval_instruction` ran a line
+#[derive(Debug)]
+enum StepOutcome {
+    Ran(),
+    Returned(),
+}
+
+// runs one more line against an existing state, as if it were appended to
+// the program. jumping back to earlier lines runs them again until the
+// program gets back to the end, within the state's time limit per call. a
+// line that fails leaves the state as it was
+fn eval_instruction(state: &mut Program, line: &str) -> Result<StepOutcome, Verdict> {
+    let lineno = state.code.instructions.len();
+    if state.returned {
+        return Err(Verdict::RuntimeError(
+            lineno,
+            "ya code already touched grass".to_string(),
+        ));
+    }
+    if line.to_lowercase().contains("based") {
+        return Err(Verdict::Based());
+    }
+    let (instruction, cost) = Program::compile_line(strip_comment(line), Program::line_cost, &[])
+        .map_err(|message| Verdict::CompileError(lineno, message))?;
+    let mut code = CompiledProgram::clone(&state.code);
+    code.instructions.push(instruction);
+    code.costs.push(cost);
+    code.lower()?;
+    let old_code = std::mem::replace(&mut state.code, Arc::new(code));
+    let snapshot = state.snapshot();
+    state.pc = lineno;
+    let result = loop {
+        if let Err(verdict) = state.execute_one() {
+            break Err(verdict);
+        }
+        let spent = state.runtime - snapshot.runtime;
+        if state.returned {
+            break Ok(StepOutcome::Returned());
+        }
+        if state.pc == lineno + 1 {
+            break Ok(StepOutcome::Ran());
+        }
+        if spent > state.time_limit {
+            break Err(Verdict::TimeLimitExceeded());
+        }
+    };
+    if result.is_err() {
+        state.restore(&snapshot);
+        state.code = old_code;
+    }
+    result
+}
+
+// what an intrinsic has spent so far. a line is only checked against the time
+// limit before it runs, so intrinsics whose cost depends on how much they do,
+// like custom opcodes working on whole arrays, tick this as they go to stop
+// right at the limit instead of finishing first and overshooting it
+struct Meter {
+    spent: usize,
+    budget: usize,
+}
+
+impl Meter {
+    // the same as ticking 1 `cost` times, so going over the limit stops right
+    // past it. work whose whole cost is known before it starts ticks it all
+    // at once, and fails before doing any of it
+    fn tick(&mut self, cost: usize) -> Result<(), Verdict> {
+        if self.spent.saturating_add(cost) > self.budget {
+            self.spent = self.spent.max(self.budget).saturating_add(1);
+            return Err(Verdict::TimeLimitExceeded());
+        }
+        self.spent += cost;
+        Ok(())
+    }
+}
+
+// for intrinsics and the instructions that cost more than their line
+impl Program {
+    fn metered<T>(
+        &mut self,
+        work: impl FnOnce(&mut Program, &mut Meter) -> Result<T, Verdict>,
+    ) -> Result<T, Verdict> {
+        let mut meter = Meter {
+            spent: 0,
+            budget: self.time_limit.saturating_sub(self.runtime),
+        };
+        let result = work(self, &mut meter);
+        self.runtime = self.runtime.saturating_add(meter.spent);
+        result
+    }
+}
+
+// invariants every run of the interpreter has to keep, checked one step at a
+// time. selftest runs them over the jury's solutions, and anything embedding
+// the interpreter can run them over its own programs and tests
+mod testkit {
+    use super::*;
+
+    // everything a run can observe, to compare two states with
+    fn state(program: &Program) -> String {
+        let variables = program
+            .variables()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>();
+        format!(
+            "pc {} calls {:?} runtime {} returned {} memory {}/{} variables {:?} outputs {:?} spat {:?}",
+            program.pc(),
+            program.call_stack,
+            program.runtime(),
+            program.returned,
+            program.memory.used,
+            program.memory.peak,
+            variables,
+            program.peek_outputs(),
+            &program.spat[program.spat_flushed..]
+        )
+    }
+
+    fn fresh(code: &Arc<CompiledProgram>, test: &TestCase) -> Program {
+        let mut program = Program::spawn(Arc::clone(code));
+        test.prepare(&mut program);
+        program
+    }
+
+    // the cost never goes down, the pc stays on a line unless something
+    // failed, nothing happens after the program returns
+    pub fn check_run(
+        code: &Arc<CompiledProgram>,
+        test: &TestCase,
+        time_limit: usize,
+    ) -> Result<(), String> {
+        let mut program = fresh(code, test);
+        while !program.returned && program.runtime() <= time_limit {
+            let runtime = program.runtime();
+            let result = program.execute_one();
+            if program.runtime() < runtime {
+                return Err(format!(
+                    "cost went down from {} to {}",
+                    runtime,
+                    program.runtime()
+                ));
+            }
+            if result.is_err() {
+                return Ok(());
+            }
+            if !program.returned && program.instruction_at(program.pc()).is_none() {
+                return Err(format!(
+                    "pc {} is off the program without an error",
+                    program.pc()
+                ));
+            }
+        }
+        if program.returned {
+            let returned = state(&program);
+            let result = program.execute(time_limit);
+            if result.is_err() || state(&program) != returned {
+                return Err("something happened after returning".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    // restoring the first snapshot gives back a pristine program, which
+    // then runs exactly like a fresh one
+    pub fn check_restore(
+        code: &Arc<CompiledProgram>,
+        test: &TestCase,
+        time_limit: usize,
+    ) -> Result<(), String> {
+        let mut program = fresh(code, test);
+        let pristine = program.snapshot();
+        let first = program.execute(time_limit).map(|_| state(&program));
+        program.restore(&pristine);
+        let mut other = fresh(code, test);
+        if state(&program) != state(&other) {
+            return Err("restoring didn't give back a pristine program".to_string());
+        }
+        let again = program.execute(time_limit).map(|_| state(&program));
+        let expected = other.execute(time_limit).map(|_| state(&other));
+        if format!("{:?}", again) != format!("{:?}", expected)
+            || format!("{:?}", again) != format!("{:?}", first)
+        {
+            return Err("a restored program ran differently from a fresh one".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn check_all(
+        code: &Arc<CompiledProgram>,
+        test: &TestCase,
+        time_limit: usize,
+    ) -> Result<(), String> {
+        check_run(code, test, time_limit)?;
+        check_restore(code, test, time_limit)
+    }
+}
+
+// the whole of judging in-process, from the contents of the input, output
+// and answer files to what ends up on stderr and the exit code, so judging
+// can be tested without running the binary and reading its memes back
+mod harness {
+    use super::*;
+
+    // files by name, which are all empty until written to
+    #[derive(Default)]
+    pub struct MemoryFiles(pub std::cell::RefCell<HashMap<String, String>>);
+
+    impl MemoryFiles {
+        pub fn new(files: &[(&str, &str)]) -> MemoryFiles {
+            let files = files
+                .iter()
+                .map(|&(path, text)| (path.to_string(), text.to_string()));
+            MemoryFiles(std::cell::RefCell::new(files.collect()))
+        }
+    }
+
+    impl FileProvider for &MemoryFiles {
+        fn read(&self, path: &str) -> Result<Vec<u8>, CheckerFail> {
+            match self.0.borrow().get(path) {
+                Some(text) => Ok(text.clone().into_bytes()),
+                None => Err(CheckerFail(format!("no file called {}", compress(path)))),
+            }
+        }
+        fn append(&self, path: &str, text: &str) -> Result<(), CheckerFail> {
+            self.0
+                .borrow_mut()
+                .entry(path.to_string())
+                .or_default()
+                .push_str(text);
+            Ok(())
+        }
+    }
+
+    // a clock that's always at the same time
+    pub struct FixedClock(pub std::time::Duration);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> std::time::Duration {
+            self.0
+        }
+    }
+
+    // what `checker <input> <output> <answer>` does with those files, except
+    // for the environment
+    pub fn judge_files(input: &str, output: &str, answer: &str) -> CheckerOutcome {
+        let files = MemoryFiles::new(&[("input", input), ("output", output), ("answer", answer)]);
+        let config = CheckerConfig::new("input", "output", "answer");
+        run_checker(config, &files, FixedClock(std::time::Duration::ZERO))
+    }
+
+    // judges a program against the jury's solution of the task
+    pub fn judge_source(task: i32, source: &str) -> CheckerOutcome {
+        match jury_solution(task) {
+            Ok(jury) => judge_files(&task.to_string(), source, &jury),
+            Err(error) => CheckerOutcome {
+                stderr: report(&Err(CheckerFail(error.0.clone()))).0 + "\n",
+                verdict: Err(error),
+                exit_code: 3,
+            },
+        }
+    }
+}
+
+// how much running a line costs, given its source
+type CostModel = fn(&str) -> usize;
+
+// cost models that `costdiff` can compare, the one used for judging is `line`
+const COST_MODELS: &[(&str, CostModel)] = &[
+    ("line", Program::line_cost),
+    ("flat", |_| 10),
+    ("tokens", |line| 3 * line.split_whitespace().count() + 5),
+];
+// called with the program state before each line runs
+type Observer = Box<dyn FnMut(&Program)>;
+
+// the way to run programs when embedding the interpreter
+struct InterpreterBuilder {
+    time_limit: usize,
+    memory_limit: usize,
+    cost_model: CostModel,
+    observer: Option<Observer>,
+    assertions: bool,
+    opcodes: Vec<(OpcodeId, Arc<Opcode>)>,
+    task: Option<i32>,
+}
+
+// not every knob is used by the checker itself, selftest drives the rest
+impl InterpreterBuilder {
+    fn new() -> Self {
+        InterpreterBuilder {
+            time_limit: usize::MAX,
+            memory_limit: usize::MAX,
+            cost_model: Program::line_cost,
+            observer: None,
+            assertions: true,
+            opcodes: Vec::new(),
+            task: None,
+        }
+    }
+    fn time_limit(mut self, time_limit: usize) -> Self {
+        self.time_limit = time_limit;
+        self
+    }
+    // in bytes, see MemoryUsage
+    fn memory_limit(mut self, memory_limit: usize) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+    fn cost_model(mut self, cost_model: CostModel) -> Self {
+        self.cost_model = cost_model;
+        self
+    }
+    fn observer(mut self, observer: impl FnMut(&Program) + 'static) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+    fn assertions(mut self, assertions: bool) -> Self {
+        self.assertions = assertions;
+        self
+    }
+    // the task the program is for, whose banned instructions don't compile
+    fn task(mut self, task: i32) -> Self {
+        self.task = Some(task);
+        self
+    }
+    // `cost` is charged on top of what the cost model says about the line
+    fn opcode(
+        mut self,
+        pattern: &str,
+        cost: usize,
+        execute: impl Fn(&mut Program, &[Operand]) -> Result<(), Verdict> + Send + Sync + 'static,
+    ) -> Self {
+        let pattern = pattern
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        assert!(pattern.iter().filter(|token| *token == "_").count() <= Operands::CAPACITY);
+        let opcode = Arc::new(Opcode {
+            pattern,
+            cost,
+            execute: Box::new(execute),
+        });
+        self.opcodes.push((OpcodeId::register(&opcode), opcode));
+        self
+    }
+    fn build(self, source: &[String]) -> Result<Interpreter, Verdict> {
+        let mut program = Program::compile_with(source, self.cost_model, &self.opcodes)?;
+        if let Some(task) = self.task {
+            program = program.check_banned(task)?;
+        }
+        if !self.assertions {
+            program.strip_for_judging();
+        }
+        Ok(Interpreter {
+            program,
+            time_limit: self.time_limit,
+            memory_limit: self.memory_limit,
+            observer: self.observer,
+        })
+    }
+}
+
+struct Interpreter {
+    program: Program,
+    time_limit: usize,
+    memory_limit: usize,
+    observer: Option<Observer>,
+}
+
+impl Interpreter {
+    fn program(&self) -> &Program {
+        &self.program
+    }
+    fn execute(&mut self) -> Result<(), Verdict> {
+        self.execute_with(|_| Ok(()))
+    }
+    fn execute_with(
+        &mut self,
+        mut after_step: impl FnMut(&mut Program) -> Result<(), Verdict>,
+    ) -> Result<(), Verdict> {
+        self.program.time_limit = self.time_limit;
+        loop {
+            if self.program.returned {
+                return Ok(());
+            }
+            if self.program.runtime > self.time_limit {
+                return Err(Verdict::TimeLimitExceeded());
+            }
+            if let Some(observer) = &mut self.observer {
+                observer(&self.program);
+            }
+            self.program.execute_one()?;
+            if self.program.memory.used > self.memory_limit {
+                return Err(Verdict::RuntimeError(
+                    self.program.code.source_line(self.program.pc),
+                    "ur variables ate all the ram".to_string(),
+                ));
+            }
+            after_step(&mut self.program)?;
+        }
+    }
+    // runs one test from a clean state and leaves the program clean again
+    fn run(&mut self, test: &TestCase) -> Verdict {
+        let pristine = self.program.snapshot();
+        test.prepare(&mut self.program);
+        let mut check = test.output_check();
+        let verdict = match self.execute_with(|program| check.feed(program)) {
+            Ok(()) => check.finish(&mut self.program),
+            Err(error) => error,
+        };
+        self.program.restore(&pristine);
+        verdict
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Pcg128 {
+    state: u128,
+    increment: u128,
+}
+
+impl Pcg128 {
+    const MULTIPLIER: u128 = 0x2360ed051fc65da44385df649fccf645;
+    fn new(state: u128, stream: u128) -> Self {
+        let increment = (stream << 1) | 1;
+        Pcg128 { state, increment }
+    }
+    fn mix(state: u128) -> u64 {
+        const SHIFT: u32 = 64;
+        const ROTATE: u32 = 122;
+        let rot = (state >> ROTATE) as u32;
+        let xsh = ((state >> SHIFT) as u64) ^ (state as u64);
+        xsh.rotate_right(rot)
+    }
+    fn next(&mut self) -> u64 {
+        self.state = self
+            .state
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.increment);
+        Self::mix(self.state)
+    }
+    // uniform from min to max inclusive, close enough for test generation
+    fn next_between(&mut self, min: i64, max: i64) -> i64 {
+        let span = (max as i128 - min as i128 + 1) as u128;
+        (min as i128 + (self.next() as u128 % span) as i128) as i64
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TestCase {
+    params: String,
+    // what the test is about, for reports and editorials
+    label: Option<String>,
+    input: Vec<Variable>,
+    // arrays are compared element by element
+    answer: Variable,
+}
+
+impl TestCase {
+    // what kind of test it is and how big
+    fn tags(&self, kind: &str) -> Vec<String> {
+        let mut tags = vec![kind.to_string()];
+        match self.size() {
+            0 => {}
+            1..=10 => tags.push("small".to_string()),
+            _ => tags.push("large".to_string()),
+        }
+        tags
+    }
+    fn labeled(self, label: &str) -> TestCase {
+        TestCase {
+            label: Some(label.to_string()),
+            ..self
+        }
+    }
+    fn describe(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{} ({})", self.params, label),
+            None => self.params.clone(),
+        }
+    }
+    // the length of the input array, or 0 for tasks without one
+    fn size(&self) -> usize {
+        self.input
+            .iter()
+            .map(|variable| match variable {
+                Variable::Array(array) => array.len(),
+                Variable::Text(text) => text.len(),
+                Variable::Grid(rows) => rows.iter().map(Vec::len).sum(),
+                Variable::Integer(_) => 0,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+    fn prepare(&self, program: &mut Program) {
+        // the same test always rolls the same numbers
+        let seed = DEFAULT_SEED ^ fnv1a(input_text(&self.input).bytes()) as u128;
+        program.rng = Pcg128::new(seed, GAMBLE_STREAM);
+        program.feed_inputs(self.input.iter().cloned());
+    }
+    fn run_and_check(&self, program: &mut Program, time_limit: usize) -> Verdict {
+        self.run_with(program, time_limit, &mut self.output_check())
+    }
+    fn run_with(
+        &self,
+        program: &mut Program,
+        time_limit: usize,
+        check: &mut OutputCheck,
+    ) -> Verdict {
+        self.prepare(program);
+        if let Err(error) = program.execute_with(time_limit, |program| check.feed(program)) {
+            return error;
+        }
+        check.finish(program)
+    }
+    fn check(&self, program: &mut Program) -> Verdict {
+        self.output_check().finish(program)
+    }
+    fn output_check(&self) -> OutputCheck<'_> {
+        OutputCheck {
+            test: self,
+            seen: 0,
+            diff: None,
+        }
+    }
+}
+
+// where a printed array first goes wrong, with None past the end of one
+struct SequenceDiff {
+    index: usize,
+    expected: Option<i64>,
+    got: Option<i64>,
+    missing: usize,
+    extra: usize,
+}
+
+impl SequenceDiff {
+    // None if the sequences are the same
+    fn new(expected: &[i64], got: &[i64]) -> Option<SequenceDiff> {
+        let index = (0..expected.len().max(got.len()))
+            .find(|&index| expected.get(index) != got.get(index))?;
+        Some(SequenceDiff {
+            index,
+            expected: expected.get(index).copied(),
+            got: got.get(index).copied(),
+            missing: expected.len().saturating_sub(got.len()),
+            extra: got.len().saturating_sub(expected.len()),
+        })
+    }
+    fn json(&self) -> String {
+        let value =
+            |value: Option<i64>| value.map_or("null".to_string(), |value| value.to_string());
+        format!(
+            "{{\"index\":{},\"expected\":{},\"got\":{},\"missing\":{},\"extra\":{}}}",
+            self.index,
+            value(self.expected),
+            value(self.got),
+            self.missing,
+            self.extra
+        )
+    }
+}
+
+impl std::fmt::Display for SequenceDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let value =
+            |value: Option<i64>| value.map_or("nothing".to_string(), |value| value.to_string());
+        write!(
+            f,
+            "git gud at index {}: expected {}, got {}",
+            self.index,
+            value(self.expected),
+            value(self.got)
+        )?;
+        if self.missing > 0 {
+            write!(f, ", {} missing", self.missing)?;
+        }
+        if self.extra > 0 {
+            write!(f, ", {} extra", self.extra)?;
+        }
+        Ok(())
+    }
+}
+
+// compares outputs as soon as they are printed, so a program that prints
+// something wrong gets stopped right there instead of running to the end
+struct OutputCheck<'a> {
+    test: &'a TestCase,
+    seen: usize,
+    diff: Option<SequenceDiff>,
+}
+
+impl OutputCheck<'_> {
+    // fails once what was printed so far can't be right anymore
+    fn feed(&mut self, program: &mut Program) -> Result<(), Verdict> {
+        for value in program.drain_outputs() {
+            self.seen += 1;
+            if self.seen > 1 {
+                return Err(Verdict::WrongAnswer("too much stuff printed".to_string()));
+            }
+            match (value, &self.test.answer) {
+                (Variable::Integer(output), Variable::Integer(answer)) if output != *answer => {
+                    return Err(Verdict::WrongAnswer("git gud".to_string()))
+                }
+                (Variable::Integer(_), Variable::Integer(_)) => {}
+                (Variable::Array(_), Variable::Integer(_)) => {
+                    return Err(Verdict::WrongAnswer(
+                        "U PRINTERD AN ENTRIE ARRAY???".to_string(),
+                    ))
+                }
+                (Variable::Integer(_), Variable::Array(_)) => {
+                    return Err(Verdict::WrongAnswer(
+                        "yeet the whole array, not a number".to_string(),
+                    ))
+                }
+                (Variable::Text(output), Variable::Text(answer)) if output != *answer => {
+                    return Err(Verdict::WrongAnswer(format!(
+                        "expected \"{}\", found \"{}\"",
+                        compress(answer),
+                        compress(&output)
+                    )))
+                }
+                (Variable::Text(_), Variable::Text(_)) => {}
+                // spat out characters are read back like a judge reads a
+                // C++ solution's output
+                (Variable::Text(output), Variable::Integer(answer))
+                    if output.trim().parse::<i64>().is_ok() =>
+                {
+                    if output.trim().parse::<i64>() != Ok(*answer) {
+                        return Err(Verdict::WrongAnswer("git gud".to_string()));
+                    }
+                }
+                (Variable::Text(output), Variable::Array(answer))
+                    if output
+                        .split_whitespace()
+                        .all(|token| token.parse::<i64>().is_ok()) =>
+                {
+                    let output = output
+                        .split_whitespace()
+                        .map(|token| token.parse().unwrap())
+                        .collect::<Vec<i64>>();
+                    if let Some(diff) = SequenceDiff::new(answer, &output) {
+                        let verdict = Verdict::WrongAnswer(diff.to_string());
+                        self.diff = Some(diff);
+                        return Err(verdict);
+                    }
+                }
+                (Variable::Array(output), Variable::Array(answer)) => {
+                    if let Some(diff) = SequenceDiff::new(answer, &output) {
+                        let verdict = Verdict::WrongAnswer(diff.to_string());
+                        self.diff = Some(diff);
+                        return Err(verdict);
+                    }
+                }
+                (Variable::Grid(output), Variable::Grid(answer)) => {
+                    if output.len() != answer.len() {
+                        return Err(Verdict::WrongAnswer(format!(
+                            "expected {} rows, found {}",
+                            answer.len(),
+                            output.len()
+                        )));
+                    }
+                    for (row, (answer, output)) in answer.iter().zip(&output).enumerate() {
+                        if let Some(diff) = SequenceDiff::new(answer, output) {
+                            return Err(Verdict::WrongAnswer(format!("row {}: {}", row, diff)));
+                        }
+                    }
+                }
+                (output, answer) => {
+                    return Err(Verdict::WrongAnswer(format!(
+                        "expected {}, found {}",
+                        answer.kind(),
+                        output.kind()
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+    fn finish(&mut self, program: &mut Program) -> Verdict {
+        match self.feed(program) {
+            Err(verdict) => verdict,
+            Ok(()) if self.seen == 0 => Verdict::WrongAnswer("print something".to_string()),
+            Ok(()) => Verdict::Correct(),
+        }
+    }
+}
+
+struct Task1();
+
+impl Task1 {
+    fn test_case(a: i64, b: i64) -> TestCase {
+        TestCase {
+            params: format!("a={} b={}", a, b),
+            label: None,
+            input: vec![Variable::Integer(a), Variable::Integer(b)],
+            answer: Variable::Integer(a + b),
+        }
+    }
+}
+
+struct Task2();
+
+impl Task2 {
+    fn test_case(a: i64) -> TestCase {
+        TestCase {
+            params: format!("a={}", a),
+            label: None,
+            input: vec![Variable::Integer(a)],
+            answer: Variable::Integer(a.abs()),
+        }
+    }
+}
+
+struct Task3();
+
+impl Task3 {
+    fn test_case(a: Vec<i64>) -> TestCase {
+        let answer = Variable::Integer(*a.iter().max().unwrap());
+        TestCase {
+            params: format!("n={}", a.len()),
+            label: None,
+            input: vec![Variable::Integer(a.len() as i64), Variable::Array(a)],
+            answer,
+        }
+    }
+}
+
+struct Task4();
+
+impl Task4 {
+    fn test_case(a: Vec<i64>, k: usize) -> TestCase {
+        let n = a.len();
+        let answer = Variable::Integer(*a.clone().select_nth_unstable(n - k).1);
+        TestCase {
+            params: format!("n={} k={}", n, k),
+            label: None,
+            input: vec![
+                Variable::Integer(n as i64),
+                Variable::Array(a),
+                Variable::Integer(k as i64),
+            ],
+            answer,
+        }
+    }
+}
+
+// a test for an input that was read from somewhere else, which has to match
+// the task's input format already
+fn external_test(task: i32, input: Vec<Variable>) -> Result<TestCase, CheckerFail> {
+    use Variable::*;
+    match (task, &input[..]) {
+        (1, &[Integer(a), Integer(b)]) => Ok(Task1::test_case(a, b)),
+        (2, &[Integer(a)]) => Ok(Task2::test_case(a)),
+        (3, [Integer(_), Array(a)]) => Ok(Task3::test_case(a.clone())),
+        (4, [Integer(_), Array(a), Integer(k)]) => Ok(Task4::test_case(a.clone(), *k as usize)),
+        _ => Err(CheckerFail(format!("input doesn't fit task {}", task))),
+    }
+}
+
+// every input of a task with values from -range to range and arrays of up
+// to max_n elements. only the order of the elements matters to the array
+// tasks, so there it is every order of n distinct values
+fn exhaustive_tests(task: i32, range: i64, max_n: usize) -> Result<Vec<TestCase>, CheckerFail> {
+    let values = || -range..=range;
+    let mut tests = Vec::new();
+    match task {
+        1 => {
+            for a in values() {
+                tests.extend(values().map(|b| Task1::test_case(a, b)));
+            }
+        }
+        2 => tests.extend(values().map(Task2::test_case)),
+        3 | 4 => {
+            for n in 1..=max_n as i64 {
+                let mut a = (1..=n).map(|value| value - (n + 1) / 2).collect::<Vec<_>>();
+                loop {
+                    match task {
+                        3 => tests.push(Task3::test_case(a.clone())),
+                        _ => tests.extend((1..=a.len()).map(|k| Task4::test_case(a.clone(), k))),
+                    }
+                    if !next_permutation(&mut a) {
+                        break;
+                    }
+                }
+            }
+        }
+        _ => return Err(CheckerFail(format!("task {} can't be enumerated", task))),
+    }
+    Ok(tests)
+}
+
+// the next order in lexicographic order, false after the last one
+fn next_permutation(a: &mut [i64]) -> bool {
+    let Some(i) = (1..a.len()).rev().find(|&i| a[i - 1] < a[i]) else {
+        return false;
+    };
+    let j = (i..a.len()).rev().find(|&j| a[i - 1] < a[j]).unwrap();
+    a.swap(i - 1, j);
+    a[i..].reverse();
+    true
+}
+
+// fixed tests that are run before the random ones, random tests alone keep
+// letting off-by-one solutions through. elements of the arrays in tasks 3 and 4
+// are distinct, so the boundaries are covered with extreme values instead
+fn corner_cases(task: i32) -> Vec<TestCase> {
+    const MAX: i64 = (1 << 59) - 1;
+    const MIN: i64 = -(1 << 59);
+    match task {
+        1 => vec![
+            Task1::test_case(0, 0).labeled("all zero"),
+            Task1::test_case(MAX, MAX).labeled("largest sum"),
+            Task1::test_case(MIN, MIN).labeled("smallest sum"),
+            Task1::test_case(MAX, MIN).labeled("opposite extremes"),
+        ],
+        2 => vec![
+            Task2::test_case(0).labeled("zero"),
+            Task2::test_case(-1).labeled("negative"),
+            Task2::test_case(MAX).labeled("largest value"),
+            Task2::test_case(MIN).labeled("smallest value"),
+        ],
+        3 => vec![
+            Task3::test_case(vec![MIN]).labeled("single element"),
+            Task3::test_case(vec![-3, -1, -2]).labeled("all negative"),
+            Task3::test_case(vec![MAX, 0, 1, 2]).labeled("answer at index 0"),
+            Task3::test_case(vec![0, 1, 2, MAX]).labeled("answer at the last index"),
+            Task3::test_case(vec![MIN, MIN + 1, MAX - 1, MAX]).labeled("extreme values"),
+        ],
+        4 => vec![
+            Task4::test_case(vec![MIN], 1).labeled("single element"),
+            Task4::test_case(vec![-5, -1, -3, -2, -4], 1).labeled("all negative, largest"),
+            Task4::test_case(vec![-5, -1, -3, -2, -4], 5).labeled("all negative, smallest"),
+            Task4::test_case(vec![1, 2, 3, 4, 5, 6], 3).labeled("increasing"),
+            Task4::test_case(vec![6, 5, 4, 3, 2, 1], 4).labeled("decreasing"),
+            Task4::test_case(vec![MAX, MIN, MAX - 1, MIN + 1], 1)
+                .labeled("extreme values, largest"),
+            Task4::test_case(vec![MAX, MIN, MAX - 1, MIN + 1], 4)
+                .labeled("extreme values, smallest"),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+// corner cases that are easier to write down as input files, in the task's
+// input format, than to build in code
+const CORNER_CASE_FILES: &[(i32, &str, &str)] = &[
+    (3, "one largest element", "1\n576460752303423487\n"),
+    (3, "two elements", "2\n-2 -1\n"),
+    (4, "one largest element", "1\n576460752303423487\n1\n"),
+    (4, "two elements, k=2", "2\n-1 -2\n2\n"),
+];
+
+fn corner_case_files(task: i32, format: &InputFormat) -> Result<Vec<TestCase>, CheckerFail> {
+    CORNER_CASE_FILES
+        .iter()
+        .filter(|&&(file_task, _, _)| file_task == task)
+        .map(|&(_, label, text)| {
+            let input = format
+                .read(text)
+                .map_err(|message| CheckerFail(format!("corner case {}: {}", label, message)))?;
+            Ok(external_test(task, input)?.labeled(label))
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+struct Bound {
+    text: String,
+    strict: bool,
+}
+
+#[derive(Clone)]
+enum FormatItem {
+    Int {
+        name: String,
+        bounds: Option<(Bound, Bound)>,
+    },
+    Array {
+        name: String,
+        len: String,
+        distinct: bool,
+        bounds: Option<(Bound, Bound)>,
+    },
+    // one word, with no whitespace in it
+    Text {
+        name: String,
+    },
+    // rows*cols integers, row by row
+    Grid {
+        name: String,
+        rows: String,
+        cols: String,
+        bounds: Option<(Bound, Bound)>,
+    },
+}
+
+// the shape of a task's input, written like
+// "int n with 1<=n<=50, array[n] a distinct with -2^59<=a<2^59, int k with 1<=k<=n"
+#[derive(Clone)]
+struct InputFormat {
+    items: Vec<FormatItem>,
+}
+
+impl Bound {
+    // an integer like -5 or -2^59, or the name of an earlier int
+    fn value(&self, ints: &HashMap<&str, i64>) -> Result<i64, String> {
+        if let Some(&value) = ints.get(self.text.as_str()) {
+            return Ok(value);
+        }
+        let (sign, number) = match self.text.strip_prefix('-') {
+            Some(number) => (-1, number),
+            None => (1, self.text.as_str()),
+        };
+        let value = match number.split_once('^') {
+            Some((base, exponent)) => base
+                .parse::<i64>()
+                .ok()
+                .zip(exponent.parse::<u32>().ok())
+                .and_then(|(base, exponent)| base.checked_pow(exponent)),
+            None => number.parse().ok(),
+        };
+        value
+            .map(|value| sign * value)
+            .ok_or_else(|| format!("cannot parse bound '{}'", compress(&self.text)))
+    }
+}
+
+impl std::fmt::Display for InputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (index, item) in self.items.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            let (name, bounds) = match item {
+                FormatItem::Int { name, bounds } => {
+                    write!(f, "int {}", name)?;
+                    (name, bounds)
+                }
+                FormatItem::Array {
+                    name,
+                    len,
+                    distinct,
+                    bounds,
+                } => {
+                    write!(f, "array[{}] {}", len, name)?;
+                    if *distinct {
+                        write!(f, " distinct")?;
+                    }
+                    (name, bounds)
+                }
+                FormatItem::Text { name } => {
+                    write!(f, "string {}", name)?;
+                    continue;
+                }
+                FormatItem::Grid {
+                    name,
+                    rows,
+                    cols,
+                    bounds,
+                } => {
+                    write!(f, "grid[{}][{}] {}", rows, cols, name)?;
+                    (name, bounds)
+                }
+            };
+            if let Some((lower, upper)) = bounds {
+                let op = |bound: &Bound| if bound.strict { "<" } else { "<=" };
+                write!(
+                    f,
+                    " with {}{}{}{}{}",
+                    lower.text,
+                    op(lower),
+                    name,
+                    op(upper),
+                    upper.text
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for InputFormat {
+    type Error = String;
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        fn parse_bounds(name: &str, constraint: &str) -> Result<(Bound, Bound), String> {
+            let mut parts = Vec::new();
+            let mut rest = constraint;
+            while let Some(position) = rest.find('<') {
+                let strict = !rest[position + 1..].starts_with('=');
+                parts.push((rest[..position].trim(), strict));
+                rest = &rest[position + if strict { 1 } else { 2 }..];
+            }
+            match parts[..] {
+                [(lower, lower_strict), (middle, upper_strict)] if middle == name => Ok((
+                    Bound {
+                        text: lower.to_string(),
+                        strict: lower_strict,
+                    },
+                    Bound {
+                        text: rest.trim().to_string(),
+                        strict: upper_strict,
+                    },
+                )),
+                _ => Err(format!(
+                    "cannot parse constraint '{}', should look like lo<={}<=hi",
+                    compress(constraint),
+                    compress(name)
+                )),
+            }
+        }
+        let mut items = Vec::new();
+        for item in string.split(',') {
+            let (declaration, constraint) = match item.split_once(" with ") {
+                Some((declaration, constraint)) => (declaration, Some(constraint)),
+                None => (item, None),
+            };
+            let tokens = declaration.split_whitespace().collect::<Vec<_>>();
+            let (kind, name, distinct) = match tokens[..] {
+                [kind, name] => (kind, name, false),
+                [kind, name, "distinct"] => (kind, name, true),
+                _ => return Err(format!("cannot parse '{}'", compress(item.trim()))),
+            };
+            let bounds = constraint
+                .map(|constraint| parse_bounds(name, constraint))
+                .transpose()?;
+            let name = name.to_string();
+            if kind == "int" && !distinct {
+                items.push(FormatItem::Int { name, bounds });
+            } else if kind == "string" && !distinct && bounds.is_none() {
+                items.push(FormatItem::Text { name });
+            } else if let Some((rows, cols)) = kind
+                .strip_prefix("grid[")
+                .and_then(|kind| kind.strip_suffix(']'))
+                .and_then(|kind| kind.split_once("]["))
+                .filter(|_| !distinct)
+            {
+                items.push(FormatItem::Grid {
+                    name,
+                    rows: rows.to_string(),
+                    cols: cols.to_string(),
+                    bounds,
+                });
+            } else if let Some(len) = kind
+                .strip_prefix("array[")
+                .and_then(|kind| kind.strip_suffix(']'))
+            {
+                items.push(FormatItem::Array {
+                    name,
+                    len: len.to_string(),
+                    distinct,
+                    bounds,
+                });
+            } else {
+                return Err(format!("unknown type '{}'", compress(kind)));
+            }
+        }
+        Ok(InputFormat { items })
+    }
+}
+
+impl InputFormat {
+    // the longest an array can be no matter what the other values are
+    fn max_len(&self, array: &str) -> Option<usize> {
+        let len = self.items.iter().find_map(|item| match item {
+            FormatItem::Array { name, len, .. } if name == array => Some(len),
+            _ => None,
+        })?;
+        self.items.iter().find_map(|item| match item {
+            FormatItem::Int {
+                name,
+                bounds: Some((_, upper)),
+            } if name == len => {
+                let max = upper.value(&HashMap::new()).ok()? - upper.strict as i64;
+                Some(max.max(0) as usize)
+            }
+            _ => None,
+        })
+    }
+    fn validate(&self, input: &[Variable]) -> Result<(), String> {
+        if input.len() != self.items.len() {
+            return Err(format!(
+                "expected {} values, found {}",
+                self.items.len(),
+                input.len()
+            ));
+        }
+        let check = |name: &str, value: i64, bounds, ints: &HashMap<&str, i64>| {
+            let Some((lower, upper)): Option<&(Bound, Bound)> = bounds else {
+                return Ok(());
+            };
+            let (min, max) = (lower.value(ints)?, upper.value(ints)?);
+            let above = if lower.strict {
+                value > min
+            } else {
+                value >= min
+            };
+            let below = if upper.strict {
+                value < max
+            } else {
+                value <= max
+            };
+            match above && below {
+                true => Ok(()),
+                false => Err(format!("{} = {} is out of range", name, value)),
+            }
+        };
+        let mut ints = HashMap::new();
+        for (item, variable) in self.items.iter().zip(input) {
+            match (item, variable) {
+                (FormatItem::Int { name, bounds }, Variable::Integer(value)) => {
+                    check(name, *value, bounds.as_ref(), &ints)?;
+                    ints.insert(name.as_str(), *value);
+                }
+                (
+                    FormatItem::Array {
+                        name,
+                        len,
+                        distinct,
+                        bounds,
+                    },
+                    Variable::Array(values),
+                ) => {
+                    let len = Bound {
+                        text: len.clone(),
+                        strict: false,
+                    }
+                    .value(&ints)?;
+                    if values.len() as i64 != len {
+                        return Err(format!(
+                            "{} should have {} elements, found {}",
+                            name,
+                            len,
+                            values.len()
+                        ));
+                    }
+                    for value in values {
+                        check(name, *value, bounds.as_ref(), &ints)?;
+                    }
+                    if *distinct && values.iter().collect::<BTreeSet<_>>().len() != values.len() {
+                        return Err(format!("elements of {} should be distinct", name));
+                    }
+                }
+                (FormatItem::Text { .. }, Variable::Text(_)) => {}
+                (
+                    FormatItem::Grid {
+                        name,
+                        rows,
+                        cols,
+                        bounds,
+                    },
+                    Variable::Grid(values),
+                ) => {
+                    let dimension = |text: &String| {
+                        Bound {
+                            text: text.clone(),
+                            strict: false,
+                        }
+                        .value(&ints)
+                    };
+                    let (rows, cols) = (dimension(rows)?, dimension(cols)?);
+                    if values.len() as i64 != rows
+                        || values.iter().any(|row| row.len() as i64 != cols)
+                    {
+                        return Err(format!("{} should be {} by {}", name, rows, cols));
+                    }
+                    for value in values.iter().flatten() {
+                        check(name, *value, bounds.as_ref(), &ints)?;
+                    }
+                }
+                (FormatItem::Int { name, .. }, variable) => {
+                    return Err(format!(
+                        "expected integer {}, found {}",
+                        name,
+                        variable.kind()
+                    ))
+                }
+                (FormatItem::Array { name, .. }, variable) => {
+                    return Err(format!(
+                        "expected array {}, found {}",
+                        name,
+                        variable.kind()
+                    ))
+                }
+                (FormatItem::Text { name }, variable) => {
+                    return Err(format!(
+                        "expected string {}, found {}",
+                        name,
+                        variable.kind()
+                    ))
+                }
+                (FormatItem::Grid { name, .. }, variable) => {
+                    return Err(format!("expected grid {}, found {}", name, variable.kind()))
+                }
+            }
+        }
+        Ok(())
+    }
+    // a random input, every value uniform within its bounds and the elements
+    // of distinct arrays all different. ints named in pinned get those values
+    fn generate(&self, rng: &mut Pcg128, pinned: &[(&str, i64)]) -> Result<Vec<Variable>, String> {
+        let mut ints = HashMap::new();
+        // the smallest and largest values allowed, inclusive
+        let range = |name: &str, bounds: &Option<(Bound, Bound)>, ints: &HashMap<&str, i64>| {
+            let (min, max) = match bounds {
+                Some((lower, upper)) => (
+                    lower.value(ints)? + lower.strict as i64,
+                    upper.value(ints)? - upper.strict as i64,
+                ),
+                None => (i64::MIN, i64::MAX),
+            };
+            match min <= max {
+                true => Ok((min, max)),
+                false => Err(format!("there's no value {} can take", name)),
+            }
+        };
+        let dimension = |text: &String, ints: &HashMap<&str, i64>| {
+            Bound {
+                text: text.clone(),
+                strict: false,
+            }
+            .value(ints)
+            .map(|len| len.max(0))
+        };
+        let mut input = Vec::new();
+        for item in &self.items {
+            match item {
+                FormatItem::Int { name, bounds } => {
+                    let value = match pinned.iter().find(|&&(pinned, _)| pinned == name) {
+                        Some(&(_, value)) => value,
+                        None => {
+                            let (min, max) = range(name, bounds, &ints)?;
+                            rng.next_between(min, max)
+                        }
+                    };
+                    ints.insert(name.as_str(), value);
+                    input.push(Variable::Integer(value));
+                }
+                FormatItem::Array {
+                    name,
+                    len,
+                    distinct,
+                    bounds,
+                } => {
+                    let len = dimension(len, &ints)?;
+                    let (min, max) = range(name, bounds, &ints)?;
+                    if *distinct && (max as i128 - min as i128) < len as i128 - 1 {
+                        return Err(format!("{} can't have {} distinct elements", name, len));
+                    }
+                    let mut values = Vec::new();
+                    let mut seen = BTreeSet::new();
+                    while (values.len() as i64) < len {
+                        let value = rng.next_between(min, max);
+                        // duplicates are rolled again
+                        if !*distinct || seen.insert(value) {
+                            values.push(value);
+                        }
+                    }
+                    input.push(Variable::Array(values));
+                }
+                FormatItem::Text { .. } => {
+                    let len = rng.next_between(1, 10);
+                    let word = (0..len)
+                        .map(|_| (b'a' + rng.next_between(0, 25) as u8) as char)
+                        .collect();
+                    input.push(Variable::Text(word));
+                }
+                FormatItem::Grid {
+                    name,
+                    rows,
+                    cols,
+                    bounds,
+                } => {
+                    let (rows, cols) = (dimension(rows, &ints)?, dimension(cols, &ints)?);
+                    let (min, max) = range(name, bounds, &ints)?;
+                    let values = (0..rows)
+                        .map(|_| (0..cols).map(|_| rng.next_between(min, max)).collect())
+                        .collect();
+                    input.push(Variable::Grid(values));
+                }
+            }
+        }
+        Ok(input)
+    }
+    // reads whitespace separated integers the way a C++ solution would
+    fn read(&self, text: &str) -> Result<Vec<Variable>, String> {
+        let mut tokens = text.split_whitespace();
+        let mut word = |name: &str| {
+            tokens
+                .next()
+                .ok_or_else(|| format!("ran out of input while reading {}", name))
+        };
+        let int = |name: &str, token: &str| -> Result<i64, String> {
+            token
+                .parse()
+                .map_err(|_| format!("cannot parse '{}' as {}", compress(token), name))
+        };
+        let mut ints = HashMap::new();
+        let mut input = Vec::new();
+        for item in &self.items {
+            match item {
+                FormatItem::Int { name, .. } => {
+                    let value = int(name, word(name)?)?;
+                    ints.insert(name.as_str(), value);
+                    input.push(Variable::Integer(value));
+                }
+                FormatItem::Array { name, len, .. } => {
+                    let len = Bound {
+                        text: len.clone(),
+                        strict: false,
+                    }
+                    .value(&ints)?;
+                    let values = (0..len)
+                        .map(|_| int(name, word(name)?))
+                        .collect::<Result<_, _>>()?;
+                    input.push(Variable::Array(values));
+                }
+                FormatItem::Text { name } => input.push(Variable::Text(word(name)?.to_string())),
+                FormatItem::Grid {
+                    name, rows, cols, ..
+                } => {
+                    let dimension = |text: &String| {
+                        Bound {
+                            text: text.clone(),
+                            strict: false,
+                        }
+                        .value(&ints)
+                    };
+                    let (rows, cols) = (dimension(rows)?, dimension(cols)?);
+                    let values = (0..rows)
+                        .map(|_| {
+                            (0..cols)
+                                .map(|_| int(name, word(name)?))
+                                .collect::<Result<_, _>>()
+                        })
+                        .collect::<Result<_, _>>()?;
+                    input.push(Variable::Grid(values));
+                }
+            }
+        }
+        if let Some(token) = tokens.next() {
+            return Err(format!("unexpected '{}' after the input", compress(token)));
+        }
+        Ok(input)
+    }
+}
+
+const DEFAULT_SEED: u128 = 0xcafef00dd15ea5e5;
+const SEED_STREAM: u128 = 0xa02bdbf7bb3c0a7ac28fa16a64abf96;
+const GAMBLE_STREAM: u128 = 0x5851f42d4c957f2d14057b7ef767814f;
+
+// `--only-tests` and `--skip-tests`, comma separated lists of test names,
+// ranges of them like `random-3-7`, and tags
+struct TestFilter {
+    only: Option<Vec<String>>,
+    skip: Vec<String>,
+}
+
+impl TestFilter {
+    fn from_options(options: &Options) -> TestFilter {
+        let list = |list: &str| list.split(',').map(String::from).collect::<Vec<_>>();
+        TestFilter {
+            only: options.get("only-tests").map(list),
+            skip: options.get("skip-tests").map(list).unwrap_or_default(),
+        }
+    }
+    fn matches(&self, name: &str, tags: &[String]) -> bool {
+        // the number at the end of a name, like the 12 of random-12
+        let numbered = |name: &str| {
+            let (prefix, number) = name.rsplit_once('-')?;
+            Some((prefix.to_string(), number.parse::<usize>().ok()?))
+        };
+        let matches = |pattern: &String| {
+            let in_range = numbered(pattern).zip(numbered(name)).and_then(
+                |((range, last), (prefix, number))| {
+                    let (range, first) = numbered(&range)?;
+                    Some(range == prefix && (first..=last).contains(&number))
+                },
+            );
+            pattern == name || tags.contains(pattern) || in_range == Some(true)
+        };
+        self.only
+            .as_ref()
+            .map_or(true, |only| only.iter().any(matches))
+            && !self.skip.iter().any(matches)
+    }
+}
+
+struct TestPlan {
+    time_limit: usize,
+    format: InputFormat,
+    corner_cases: Vec<TestCase>,
+    random: Vec<TestCase>,
+}
+
+impl TestPlan {
+    fn tests(&self) -> impl Iterator<Item = (&'static str, &TestCase)> {
+        let corner_cases = self.corner_cases.iter().map(|test| ("corner", test));
+        let random = self.random.iter().map(|test| ("random", test));
+        corner_cases.chain(random)
+    }
+    // names of the tests that stay the same when tests are added before them:
+    // corner cases go by their labels and random tests by their position
+    // among the random ones, which don't depend on the corner cases
+    fn names(&self) -> impl Iterator<Item = String> + '_ {
+        let corner_cases =
+            self.corner_cases
+                .iter()
+                .enumerate()
+                .map(|(index, test)| match &test.label {
+                    Some(label) => slug(label),
+                    None => format!("corner-{}", index + 1),
+                });
+        let random = (1..=self.random.len()).map(|index| format!("random-{}", index));
+        corner_cases.chain(random)
+    }
+    // the tests a filter lets through, with their positions in the whole plan
+    // counting from 1
+    fn select(&self, filter: &TestFilter) -> Result<Vec<(usize, &TestCase)>, CheckerFail> {
+        let selected = self
+            .tests()
+            .zip(self.names())
+            .enumerate()
+            .filter(|(_, ((kind, test), name))| filter.matches(name, &test.tags(kind)))
+            .map(|(index, ((_, test), _))| (index + 1, test))
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            return Err(CheckerFail(
+                "--only-tests and --skip-tests leave no tests to run".to_string(),
+            ));
+        }
+        Ok(selected)
+    }
+    fn check_format(&self) -> Result<(), CheckerFail> {
+        let mut names = BTreeSet::new();
+        for name in self.names() {
+            if !names.insert(name.clone()) {
+                return Err(CheckerFail(format!("two tests are named {}", name)));
+            }
+        }
+        for (index, (_, test)) in self.tests().enumerate() {
+            if let Err(message) = self.format.validate(&test.input) {
+                return Err(CheckerFail(format!(
+                    "test #{} doesn't match the input format: {}",
+                    index + 1,
+                    message
+                )));
+            }
+        }
+        Ok(())
+    }
+    // the verdict of the first failed test
+    fn judge(&self, interpreter: &mut Interpreter, precedence: &VerdictPrecedence) -> Verdict {
+        if let VerdictPrecedence::First = precedence {
+            for (_, test) in self.tests() {
+                match interpreter.run(test) {
+                    Verdict::Correct() => continue,
+                    verdict => return verdict,
+                }
+            }
+            return Verdict::Correct();
+        }
+        let verdicts = self
+            .tests()
+            .map(|(_, test)| interpreter.run(test))
+            .collect::<Vec<_>>();
+        precedence.headline(&verdicts)
+    }
+}
+
+fn test_plan(task: i32, seed: u128) -> Result<TestPlan, CheckerFail> {
+    let mut rng = Pcg128::new(seed, SEED_STREAM);
+    // the random tests are generated from the input format, each with the
+    // values it pins down, like the size of the array
+    let mut pinned = Vec::new();
+    let (time_limit, format) = match task {
+        1 => {
+            pinned.resize(10, Vec::new());
+            (100000, "int a with -2^59<=a<2^59, int b with -2^59<=b<2^59")
+        }
+        2 => {
+            pinned.resize(10, Vec::new());
+            (100000, "int a with -2^59<=a<2^59")
+        }
+        3 => {
+            for n in 1..=50 {
+                pinned.push(vec![("n", n)]);
+            }
+            (
+                100000,
+                "int n with 1<=n<=50, array[n] a distinct with -2^59<=a<2^59",
+            )
+        }
+        4 => {
+            for n in 1..=50 {
+                for _ in 0..(25 / n + 1) {
+                    pinned.push(vec![("n", n)]);
+                }
+            }
+            (
+                2500000,
+                "int n with 1<=n<=50, array[n] a distinct with -2^59<=a<2^59, int k with 1<=k<=n",
+            )
+        }
+        _ => {
+            return Err(CheckerFail(format!("unknown task id {}", task)));
+        }
+    };
+    let format = InputFormat::try_from(format).map_err(CheckerFail)?;
+    let random = pinned
+        .iter()
+        .map(|pinned| {
+            let input = format.generate(&mut rng, pinned).map_err(CheckerFail)?;
+            external_test(task, input)
+        })
+        .collect::<Result<_, _>>()?;
+    let mut corner_cases = corner_cases(task);
+    corner_cases.extend(corner_case_files(task, &format)?);
+    let plan = TestPlan {
+        time_limit,
+        format,
+        corner_cases,
+        random,
+    };
+    // catches generators drifting away from what the statement promises
+    plan.check_format()?;
+    Ok(plan)
+}
+
+// test packs are test plans flattened into little-endian integers and
+// length-prefixed strings, tagged with the checker they were made by since
+// the generators can change with it
+const PACK_MAGIC: &[u8] = b"based pack\n";
+
+// a hash of the running checker binary, which changes with every rebuild
+fn checker_fingerprint() -> Result<i64, CheckerFail> {
+    let binary = std::fs::read(std::env::current_exe()?)?;
+    Ok(fnv1a(binary) as i64)
+}
+
+struct PackWriter(Vec<u8>);
+
+impl PackWriter {
+    fn int(&mut self, value: i64) {
+        self.0.extend(value.to_le_bytes());
+    }
+    fn string(&mut self, string: &str) {
+        self.int(string.len() as i64);
+        self.0.extend(string.bytes());
+    }
+    fn variable(&mut self, variable: &Variable) {
+        match variable {
+            Variable::Integer(value) => {
+                self.int(0);
+                self.int(*value);
+            }
+            Variable::Array(values) => {
+                self.int(1);
+                self.int(values.len() as i64);
+                values.iter().for_each(|&value| self.int(value));
+            }
+            Variable::Text(text) => {
+                self.int(2);
+                self.string(text);
+            }
+            Variable::Grid(rows) => {
+                self.int(3);
+                self.int(rows.len() as i64);
+                for row in rows {
+                    self.int(row.len() as i64);
+                    row.iter().for_each(|&value| self.int(value));
+                }
+            }
+        }
+    }
+    fn variables(&mut self, variables: &[Variable]) {
+        self.int(variables.len() as i64);
+        variables
+            .iter()
+            .for_each(|variable| self.variable(variable));
+    }
+    fn tests(&mut self, tests: &[TestCase]) {
+        self.int(tests.len() as i64);
+        for test in tests {
+            self.string(&test.params);
+            self.string(test.label.as_deref().unwrap_or(""));
+            self.variables(&test.input);
+            self.variable(&test.answer);
+        }
+    }
+}
+
+struct PackReader<'a>(&'a [u8]);
+
+impl PackReader<'_> {
+    fn int(&mut self) -> Result<i64, CheckerFail> {
+        if self.0.len() < 8 {
+            return Err(CheckerFail("test pack ends too early".to_string()));
+        }
+        let (bytes, rest) = self.0.split_at(8);
+        self.0 = rest;
+        Ok(i64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+    fn len(&mut self) -> Result<usize, CheckerFail> {
+        match usize::try_from(self.int()?) {
+            Ok(len) if len <= self.0.len() => Ok(len),
+            _ => Err(CheckerFail("test pack is corrupted".to_string())),
+        }
+    }
+    fn string(&mut self) -> Result<String, CheckerFail> {
+        let len = self.len()?;
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+    fn variable(&mut self) -> Result<Variable, CheckerFail> {
+        match self.int()? {
+            0 => Ok(Variable::Integer(self.int()?)),
+            1 => Ok(Variable::Array(
+                (0..self.len()?)
+                    .map(|_| self.int())
+                    .collect::<Result<_, _>>()?,
+            )),
+            2 => Ok(Variable::Text(self.string()?)),
+            3 => Ok(Variable::Grid(
+                (0..self.len()?)
+                    .map(|_| (0..self.len()?).map(|_| self.int()).collect())
+                    .collect::<Result<_, _>>()?,
+            )),
+            _ => Err(CheckerFail("test pack is corrupted".to_string())),
+        }
+    }
+    fn variables(&mut self) -> Result<Vec<Variable>, CheckerFail> {
+        (0..self.len()?).map(|_| self.variable()).collect()
+    }
+    fn tests(&mut self) -> Result<Vec<TestCase>, CheckerFail> {
+        (0..self.len()?)
+            .map(|_| {
+                let params = self.string()?;
+                let label = Some(self.string()?).filter(|label| !label.is_empty());
+                let input = self.variables()?;
+                let answer = self.variable()?;
+                Ok(TestCase {
+                    params,
+                    label,
+                    input,
+                    answer,
+                })
+            })
+            .collect()
+    }
+}
+
+impl TestPlan {
+    fn write_pack(&self, task: i32) -> Result<Vec<u8>, CheckerFail> {
+        let mut writer = PackWriter(PACK_MAGIC.to_vec());
+        writer.int(checker_fingerprint()?);
+        writer.int(task as i64);
+        writer.int(self.time_limit as i64);
+        writer.string(&self.format.to_string());
+        writer.tests(&self.corner_cases);
+        writer.tests(&self.random);
+        Ok(writer.0)
+    }
+    fn read_pack(bytes: &[u8], task: i32) -> Result<TestPlan, CheckerFail> {
+        let Some(bytes) = bytes.strip_prefix(PACK_MAGIC) else {
+            return Err(CheckerFail("not a test pack".to_string()));
+        };
+        let mut reader = PackReader(bytes);
+        if reader.int()? != checker_fingerprint()? {
+            return Err(CheckerFail(
+                "test pack was made by another version of the checker".to_string(),
+            ));
+        }
+        let pack_task = reader.int()?;
+        if pack_task != task as i64 {
+            return Err(CheckerFail(format!(
+                "test pack is for task {}, not task {}",
+                pack_task, task
+            )));
+        }
+        let plan = TestPlan {
+            time_limit: reader.int()? as usize,
+            format: reader.string()?.as_str().try_into().map_err(CheckerFail)?,
+            corner_cases: reader.tests()?,
+            random: reader.tests()?,
+        };
+        plan.check_format()?;
+        Ok(plan)
+    }
+}
+
+// which verdict is reported when tests fail in different ways, to match the
+// judge being mimicked: the one of the first failing test, or the most
+// severe one given a list of codes from most to least severe
+enum VerdictPrecedence {
+    First,
+    Severity(Vec<&'static str>),
+}
+
+// judging through the usual checker arguments takes the policy from here
+const PRECEDENCE_VARIABLE: &str = "BASED_VERDICT_PRECEDENCE";
+
+impl VerdictPrecedence {
+    const CODES: &'static [&'static str] = &["WA", "TLE", "RE", "CE", "BASED", "FAIL"];
+    fn parse(text: &str) -> Result<Self, CheckerFail> {
+        if text == "first" {
+            return Ok(VerdictPrecedence::First);
+        }
+        text.split(',')
+            .map(|code| {
+                let code = code.trim().to_uppercase();
+                Self::CODES
+                    .iter()
+                    .find(|&&known| known == code)
+                    .copied()
+                    .ok_or_else(|| CheckerFail(format!("unknown verdict {}", compress(&code))))
+            })
+            .collect::<Result<_, _>>()
+            .map(VerdictPrecedence::Severity)
+    }
+    fn from_env() -> Result<Self, CheckerFail> {
+        match std::env::var(PRECEDENCE_VARIABLE) {
+            Ok(text) => Self::parse(&text),
+            Err(_) => Ok(VerdictPrecedence::First),
+        }
+    }
+    // the verdict of every test in order, AC if all of them passed
+    fn headline<'a>(&self, verdicts: impl IntoIterator<Item = &'a Verdict>) -> Verdict {
+        let failures = verdicts
+            .into_iter()
+            .filter(|verdict| !matches!(verdict, Verdict::Correct()));
+        let headline = match self {
+            VerdictPrecedence::First => failures.into_iter().next(),
+            // codes that aren't listed come last, ties go to the earlier test
+            VerdictPrecedence::Severity(codes) => failures.min_by_key(|verdict| {
+                codes
+                    .iter()
+                    .position(|&code| code == verdict.code())
+                    .unwrap_or(codes.len())
+            }),
+        };
+        headline.cloned().unwrap_or(Verdict::Correct())
+    }
+}
+
+// judging through the usual checker arguments takes the pack from here
+const TEST_PACK_VARIABLE: &str = "BASED_TEST_PACK";
+
+fn load_test_plan(task: i32, pack: Option<&str>) -> Result<TestPlan, CheckerFail> {
+    match pack {
+        Some(path) => TestPlan::read_pack(&std::fs::read(path)?, task),
+        None => test_plan(task, DEFAULT_SEED),
+    }
+}
+
+fn pack(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let out = options.require("out")?;
+    let plan = test_plan(task, DEFAULT_SEED)?;
+    std::fs::write(out, plan.write_pack(task)?)?;
+    println!(
+        "packed {} tests for task {} into {}",
+        plan.tests().count(),
+        task,
+        out
+    );
+    Ok(())
+}
+
+struct Subtask {
+    points: u32,
+    max_n: usize,
+    prerequisites: &'static [usize],
+}
+
+// keywords from INSTRUCTION_DOCS that a task doesn't allow
+fn banned_instructions(task: i32) -> &'static [&'static str] {
+    match task {
+        2 => &["good vibes only", "goat", "flop"],
+        3 => &["goat", "sort it out"],
+        4 => &["sort it out"],
+        _ => &[],
+    }
+}
+
+// a test belongs to every subtask whose constraint it satisfies, and a subtask
+// only scores if all of its tests and all of its prerequisites pass
+fn subtasks(task: i32) -> Vec<Subtask> {
+    match task {
+        3 | 4 => vec![
+            Subtask {
+                points: 40,
+                max_n: 10,
+                prerequisites: &[],
+            },
+            Subtask {
+                points: 60,
+                max_n: 50,
+                prerequisites: &[0],
+            },
+        ],
+        _ => vec![Subtask {
+            points: 100,
+            max_n: usize::MAX,
+            prerequisites: &[],
+        }],
+    }
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, CheckerFail> {
+    let reader = BufReader::new(std::fs::File::open(filename)?);
+    Ok(reader.lines().collect::<Result<Vec<_>, _>>()?)
+}
+
+fn read_program(filename: &str) -> Result<Result<Program, Verdict>, CheckerFail> {
+    Ok(Program::compile(&read_lines(filename)?))
+}
+
+fn judge(task: i32, lines: &[String]) -> Result<Verdict, CheckerFail> {
+    let pack = std::env::var(TEST_PACK_VARIABLE).ok();
+    let plan = load_test_plan(task, pack.as_deref())?;
+    let precedence = VerdictPrecedence::from_env()?;
+    Ok(judge_on(&plan, &precedence, task, lines))
+}
+
+fn judge_on(
+    plan: &TestPlan,
+    precedence: &VerdictPrecedence,
+    task: i32,
+    lines: &[String],
+) -> Verdict {
+    let interpreter = InterpreterBuilder::new()
+        .time_limit(plan.time_limit)
+        .assertions(false)
+        .task(task)
+        .build(lines);
+    match interpreter {
+        Ok(mut interpreter) => plan.judge(&mut interpreter, precedence),
+        Err(compile_error) => compile_error,
+    }
+}
+
+struct Options {
+    named: Vec<(String, Option<String>)>,
+    positional: Vec<String>,
+}
+
+impl Options {
+    // every `--name` takes a value unless it is listed in `switches`
+    fn parse(args: &[String], switches: &[&str]) -> Result<Options, CheckerFail> {
+        let mut options = Options {
+            named: Vec::new(),
+            positional: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                let value = if switches.contains(&name) {
+                    None
+                } else {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| CheckerFail(format!("--{} needs a value", name)))?;
+                    Some(value.clone())
+                };
+                options.named.push((name.to_string(), value));
+            } else {
+                options.positional.push(arg.clone());
+            }
+        }
+        Ok(options)
+    }
+    fn get(&self, name: &str) -> Option<&str> {
+        self.named
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+    fn has(&self, name: &str) -> bool {
+        self.named.iter().any(|(key, _)| key == name)
+    }
+    fn require(&self, name: &str) -> Result<&str, CheckerFail> {
+        self.get(name)
+            .ok_or_else(|| CheckerFail(format!("missing required option --{}", name)))
+    }
+    // falls back to the program's `fr fr task` directive
+    fn task(&self, program: Option<&Program>) -> Result<i32, CheckerFail> {
+        match program.and_then(|program| program.metadata().task) {
+            Some(task) if self.get("task").is_none() => Ok(task),
+            _ => Ok(self.require("task")?.parse()?),
+        }
+    }
+}
+
+fn parse_seed(string: &str) -> Result<u128, CheckerFail> {
+    match string.strip_prefix("0x") {
+        Some(hex) => Ok(u128::from_str_radix(hex, 16)?),
+        None => Ok(string.parse()?),
+    }
+}
+
+fn plan(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let seed = match options.get("seed") {
+        Some(seed) => parse_seed(seed)?,
+        None => DEFAULT_SEED,
+    };
+    let plan = test_plan(task, seed)?;
+    println!(
+        "task {}: {} tests, time limit {}, seed {:#x}, stream {:#x}",
+        task,
+        plan.corner_cases.len() + plan.random.len(),
+        plan.time_limit,
+        seed,
+        SEED_STREAM
+    );
+    println!("input: {}", plan.format);
+    if !banned_instructions(task).is_empty() {
+        println!("banned: {}", banned_instructions(task).join(", "));
+    }
+    for (index, ((kind, test), name)) in plan.tests().zip(plan.names()).enumerate() {
+        println!(
+            "#{} {} [{}] {}",
+            index + 1,
+            name,
+            test.tags(kind).join(", "),
+            test.params
+        );
+    }
+    Ok(())
+}
+
+fn validate(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one input file".to_string()));
+    };
+    read_test(task, &test_plan(task, DEFAULT_SEED)?.format, filename)?;
+    println!("{}: ok", filename);
+    Ok(())
+}
+
+fn read_test(task: i32, format: &InputFormat, filename: &str) -> Result<TestCase, CheckerFail> {
+    let input = format
+        .read(&std::fs::read_to_string(filename)?)
+        .and_then(|input| format.validate(&input).map(|_| input))
+        .map_err(|message| CheckerFail(format!("{}: {}", filename, message)))?;
+    external_test(task, input)
+}
+
+// writes the expected answer for an input file, so that the test can be used
+// by judges that don't run this checker
+fn answer(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one input file".to_string()));
+    };
+    let plan = test_plan(task, DEFAULT_SEED)?;
+    let test = read_test(task, &plan.format, filename)?;
+    let answer = match options.get("solution") {
+        None => test.answer,
+        Some(solution) => {
+            let mut program = read_program(solution)?.map_err(|verdict| {
+                CheckerFail(format!("jury's solution failed with verdict {:?}", verdict))
+            })?;
+            test.prepare(&mut program);
+            program.execute(plan.time_limit).map_err(|verdict| {
+                CheckerFail(format!("jury's solution failed with verdict {:?}", verdict))
+            })?;
+            match &program.drain_outputs()[..] {
+                [output] => output.clone(),
+                _ => {
+                    return Err(CheckerFail(
+                        "jury's solution should print exactly one value".to_string(),
+                    ))
+                }
+            }
+        }
+    };
+    match options.get("out") {
+        Some(out) => std::fs::write(out, format!("{}\n", answer))?,
+        None => println!("{}", answer),
+    }
+    Ok(())
+}
+
+const TASKS: [i32; 4] = [1, 2, 3, 4];
+
+// the jury's solutions are read from solutions/ when they're needed, judging
+// a submission only needs the answer file
+fn jury_solution(task: i32) -> Result<String, CheckerFail> {
+    if !TASKS.contains(&task) {
+        return Err(CheckerFail(format!("unknown task id {}", task)));
+    }
+    let path = format!("solutions/{}-ac", task);
+    std::fs::read_to_string(&path).map_err(|error| {
+        CheckerFail(format!(
+            "can't read the jury's solution {}: {}",
+            path, error
+        ))
+    })
+}
+
+// small programs whose exact cost is known, so that changes to the cost model
+// show up in selftest, and whose running time calibrates cost against time
+struct CostFixture {
+    name: &'static str,
+    source: &'static str,
+    input: Vec<Variable>,
+    runtime: usize,
+}
+
+fn cost_corpus() -> Vec<CostFixture> {
+    vec![
+        CostFixture {
+            name: "straight line",
+            source: "yoink a\nbruh b is lowkey just a\n*slaps a on top of b*\nrip this b fell off by 1\nyeet b\ngo touch some grass\n",
+            input: vec![Variable::Integer(7)],
+            runtime: 130,
+        },
+        CostFixture {
+            name: "commented straight line",
+            source: "// doubles a, then takes 1 off\nyoink a // the only input\nbruh b is lowkey just a\n*slaps a on top of b*\nrip this b fell off by 1\nyeet b //yeet \"b\"\ngo touch some grass\n",
+            input: vec![Variable::Integer(7)],
+            runtime: 135,
+        },
+        CostFixture {
+            name: "countdown",
+            source: "yoink n\nvibe check n ratios 0\nsimp for 5\ngo touch some grass\nrip this n fell off by 1\nsimp for 2\n",
+            input: vec![Variable::Integer(1000)],
+            runtime: 85062,
+        },
+        CostFixture {
+            name: "spaced out countdown",
+            source: "fr fr version 2\nyoink n\n\nvibe check n ratios 0\nsimp for 6\ngo touch some grass\n\nrip this n fell off by 1\nsimp for 3\n",
+            input: vec![Variable::Integer(1000)],
+            runtime: 85082,
+        },
+        CostFixture {
+            name: "expression",
+            source: "yoink n\nbruh s is lowkey just 0\nvibe check n ratios 0\nsimp for 6\ngo touch some grass\nbruh s is lowkey just s + n\nrip this n fell off by 1\nsimp for 3\n",
+            input: vec![Variable::Integer(1000)],
+            runtime: 122090,
+        },
+        CostFixture {
+            name: "array scan",
+            // a copy of the jury's solution of task 3, so its cost stays put
+            source: "yoink n\nyoink a\nbruh m is lowkey just a[0]\nbruh i is lowkey just 1\nvibe check n ratios i\nsimp for 9\nyeet m\ngo touch some grass\nvibe check a[i] ratios m\nbruh m is lowkey just a[i]\n*slaps 1 on top of i*\nsimp for 5\n",
+            input: vec![Variable::Integer(50), Variable::Array((0..50).collect())],
+            runtime: 7102,
+        },
+        CostFixture {
+            name: "yoink in bulk",
+            source: "yoink a in bulk\ngo touch some grass\n",
+            input: (0..=50).rev().map(Variable::Integer).collect(),
+            runtime: 94,
+        },
+    ]
+}
+
+impl CostFixture {
+    fn run(&self) -> Result<Program, CheckerFail> {
+        let lines = self.source.lines().map(String::from).collect::<Vec<_>>();
+        let mut program = Program::compile(&lines)
+            .map_err(|verdict| CheckerFail(format!("{}: {}", self.name, verdict)))?;
+        program.feed_inputs(self.input.iter().cloned());
+        program
+            .execute(usize::MAX)
+            .map_err(|verdict| CheckerFail(format!("{}: {}", self.name, verdict)))?;
+        Ok(program)
+    }
+}
+
+// measures how long a unit of cost takes on this machine, to pick time
+// limits on judges that limit wall time
+fn calibrate(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let iterations = options.get("iterations").unwrap_or("100").parse::<u32>()?;
+    let mut total_runtime = 0;
+    let mut total_elapsed = std::time::Duration::ZERO;
+    for fixture in cost_corpus() {
+        let allocated = allocations();
+        let started = std::time::Instant::now();
+        for _ in 0..iterations {
+            fixture.run()?;
+        }
+        let elapsed = started.elapsed();
+        let allocated = allocations().zip(allocated).map(|(after, before)| {
+            format!(
+                ", {} allocations per run",
+                (after - before) / iterations as usize
+            )
+        });
+        let runtime = fixture.runtime * iterations as usize;
+        println!(
+            "{}: {:.2} ns per cost{}",
+            fixture.name,
+            elapsed.as_nanos() as f64 / runtime as f64,
+            allocated.unwrap_or_default()
+        );
+        total_runtime += runtime;
+        total_elapsed += elapsed;
+    }
+    let per_cost = total_elapsed.as_nanos() as f64 / total_runtime as f64;
+    println!("overall: {:.2} ns per cost", per_cost);
+    println!(
+        "instruction size: {} bytes",
+        std::mem::size_of::<Instruction>()
+    );
+    for task in TASKS {
+        let plan = test_plan(task, DEFAULT_SEED)?;
+        println!(
+            "task {}: at most {:.1} ms for {} tests",
+            task,
+            per_cost * (plan.time_limit * plan.tests().count()) as f64 / 1e6,
+            plan.tests().count()
+        );
+    }
+    Ok(())
+}
+
+// how long the checker may take on Polygon before it gets killed, which
+// covers generating the tests and running both the jury's solution and the
+// submission on all of them
+const CHECKER_TIME_BUDGET_MS: u64 = 10000;
+
+// judges a sample of the tests and scales the time up to the whole plan
+fn dry_run(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let sample = options
+        .get("sample")
+        .unwrap_or("10")
+        .parse::<usize>()?
+        .max(1);
+    let budget = match options.get("budget") {
+        Some(budget) => budget.parse::<u64>()?,
+        None => CHECKER_TIME_BUDGET_MS,
+    };
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let lines = read_lines(filename)?;
+    let task = options.task(Program::compile(&lines).as_ref().ok())?;
+    let started = std::time::Instant::now();
+    let plan = load_test_plan(task, options.get("pack"))?;
+    let generating = started.elapsed();
+    let tests = plan.tests().map(|(_, test)| test).collect::<Vec<_>>();
+    let sampled = (0..sample.min(tests.len()))
+        .map(|i| tests[i * tests.len() / sample.min(tests.len())])
+        .collect::<Vec<_>>();
+    let jury = jury_solution(task)?
+        .lines()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    let mut judging = std::time::Duration::ZERO;
+    for (name, lines) in [("jury's solution", &jury), ("submission", &lines)] {
+        let interpreter = InterpreterBuilder::new()
+            .time_limit(plan.time_limit)
+            .assertions(false)
+            .task(task)
+            .build(lines);
+        let Ok(mut interpreter) = interpreter else {
+            println!("{}: compile error, takes no time", name);
+            continue;
+        };
+        let started = std::time::Instant::now();
+        for test in &sampled {
+            interpreter.run(test);
+        }
+        let elapsed = started.elapsed() * tests.len() as u32 / sampled.len() as u32;
+        println!(
+            "{}: ~{} ms for {} tests",
+            name,
+            elapsed.as_millis(),
+            tests.len()
+        );
+        judging += elapsed;
+    }
+    let total = (generating + judging).as_millis() as u64;
+    println!(
+        "generating tests: {} ms, total: ~{} ms, sampled {} of {} tests",
+        generating.as_millis(),
+        total,
+        sampled.len(),
+        tests.len()
+    );
+    if total > budget {
+        eprintln!(
+            "warning: that's over the checker budget of {} ms, the judge will kill it",
+            budget
+        );
+    }
+    Ok(())
+}
+
+fn demo(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let solution = jury_solution(task)?;
+    print!("{}", solution);
+    let lines = solution.lines().map(String::from).collect::<Vec<_>>();
+    println!("\n{}", judge(task, &lines)?);
+    Ok(())
+}
+
+// every jury solution should pass its own task
+fn selftest(_: &[String]) -> Result<(), CheckerFail> {
+    let mut failed = false;
+    for task in TASKS {
+        let lines = jury_solution(task)?
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let outcome = harness::judge_source(task, &jury_solution(task)?);
+        let verdict = outcome.verdict?;
+        failed |= outcome.exit_code != 0;
+        println!("task {}: {} {}", task, verdict.code(), verdict);
+        let code = Program::compile(&lines)
+            .map_err(|verdict| CheckerFail(verdict.to_string()))?
+            .code;
+        let plan = test_plan(task, DEFAULT_SEED)?;
+        let broken = plan.tests().find_map(|(_, test)| {
+            Some((
+                test,
+                testkit::check_all(&code, test, plan.time_limit).err()?,
+            ))
+        });
+        failed |= broken.is_some();
+        match broken {
+            Some((test, message)) => println!(
+                "invariants of task {}: {} on {}",
+                task,
+                message,
+                test.describe()
+            ),
+            None => println!("invariants of task {}: ok", task),
+        }
+    }
+    // a submission that never gets past compiling still goes all the way to
+    // stderr and the exit code
+    let outcome = harness::judge_source(1, "yeet based");
+    let ok = outcome.exit_code == 1 && outcome.stderr == format!("{}\n", Verdict::Based());
+    failed |= !ok;
+    println!("judging pipeline: {}", if ok { "ok" } else { "broken" });
+    // so does the audit log, with the time from the clock it's given
+    let jury = jury_solution(2)?;
+    let files = harness::MemoryFiles::new(&[("input", "2"), ("output", &jury), ("answer", &jury)]);
+    let config = CheckerConfig {
+        audit_log: Some("audit".to_string()),
+        ..CheckerConfig::new("input", "output", "answer")
+    };
+    let clock = harness::FixedClock(std::time::Duration::from_secs(1234));
+    let outcome = run_checker(config, &files, clock);
+    let ok = outcome.exit_code == 0
+        && files.0.borrow().get("audit").is_some_and(|record| {
+            record.contains("\"timestamp\":1234,") && record.contains("\"verdict\":\"AC\"")
+        });
+    failed |= !ok;
+    println!("audit log: {}", if ok { "ok" } else { "broken" });
+    // no task takes a grid yet, so one goes through the whole way by hand
+    let format =
+        InputFormat::try_from("int n, int m, grid[n][m] g with 0<=g<10").map_err(CheckerFail)?;
+    let test = TestCase {
+        params: "a 2 by 2 grid".to_string(),
+        label: None,
+        input: format.read("2 2\n1 2\n3 4").map_err(CheckerFail)?,
+        answer: Variable::Grid(vec![vec![1, 2], vec![2, 4]]),
+    };
+    let lines = [
+        "yoink n",
+        "yoink m",
+        "yoink g",
+        "bruh g[1][0] is lowkey just g[0][1]",
+        "bruh g[g[0][0]][g[0][0]] is lowkey just g[1][1]",
+        "yeet g",
+        "go touch some grass",
+    ]
+    .map(String::from);
+    let code = Program::compile(&lines)
+        .map_err(|verdict| CheckerFail(verdict.to_string()))?
+        .code;
+    let verdict = test.run_and_check(&mut Program::spawn(code), 1000);
+    let ok = format.validate(&test.input).is_ok()
+        && format.read(&input_text(&test.input)).as_ref() == Ok(&test.input)
+        && matches!(verdict, Verdict::Correct());
+    failed |= !ok;
+    println!("grids: {}", if ok { "ok" } else { "broken" });
+    // with as many values as elements, a distinct array has to be an order of
+    // all of them, and with fewer it can't be generated at all
+    let format = InputFormat::try_from("int n with 1<=n<=10, array[n] a distinct with 0<=a<n")
+        .map_err(CheckerFail)?;
+    let mut rng = Pcg128::new(DEFAULT_SEED, SEED_STREAM);
+    let ok = (0..20).all(|_| {
+        format
+            .generate(&mut rng, &[("n", 10)])
+            .is_ok_and(|input| format.validate(&input).is_ok())
+    }) && InputFormat::try_from("int n, array[n] a distinct with 0<=a<3")
+        .map_err(CheckerFail)?
+        .generate(&mut rng, &[("n", 4)])
+        .is_err();
+    failed |= !ok;
+    println!("distinct arrays: {}", if ok { "ok" } else { "broken" });
+    // tests are picked by names that don't move when corner cases are added,
+    // and picking none at all is a mistake rather than a pass
+    let plan = test_plan(3, DEFAULT_SEED)?;
+    let filter = |only: &str, skip: &str| TestFilter {
+        only: Some(only.split(',').map(String::from).collect()),
+        skip: skip.split(',').map(String::from).collect(),
+    };
+    let picked = |filter| {
+        plan.select(&filter).map(|selected| {
+            selected
+                .iter()
+                .map(|&(_, test)| test.params.clone())
+                .collect::<Vec<_>>()
+        })
+    };
+    let ok = picked(filter("random-2-4,single-element", "random-3")).ok()
+        == Some(vec![
+            "n=1".to_string(),
+            "n=2".to_string(),
+            "n=4".to_string(),
+        ])
+        && picked(filter("corner", "corner")).is_err();
+    failed |= !ok;
+    println!("test filters: {}", if ok { "ok" } else { "broken" });
+    // reduce keeps jumps on the same instructions, blank lines and comments
+    // taking up no instructions from version 2 on
+    let split = |source: &str| source.lines().map(String::from).collect::<Vec<_>>();
+    let ok = remove_lines(
+        &split("fr fr version 2\nyoink n\n\nvibe check n ratios 0\nsimp for 6 // done\n\nyeet n\ngo touch some grass"),
+        6..7,
+    ) == split("fr fr version 2\nyoink n\n\nvibe check n ratios 0\nsimp for 5 // done\n\ngo touch some grass");
+    failed |= !ok;
+    println!("reduced jumps: {}", if ok { "ok" } else { "broken" });
+    // a judged `rage quit` can't be used to print the tests
+    let ok = judge(3, &split("yoink n\nrage quit n"))?
+        .to_string()
+        .contains("(value hidden when judging)");
+    failed |= !ok;
+    println!("hidden rage quit: {}", if ok { "ok" } else { "broken" });
+    // and neither can a jump to a line that doesn't exist
+    let ok = judge(
+        3,
+        &split("yoink n\nbruh x is lowkey just 0 - n\nsimp for x"),
+    )?
+    .to_string()
+    .contains("line (value hidden when judging) goes nowhere");
+    failed |= !ok;
+    println!("hidden jump target: {}", if ok { "ok" } else { "broken" });
+    // a constant can have a sign in front, `+` as well as `-`
+    let ok = [
+        ("bruh a is lowkey just +5", "bruh a is lowkey just 5"),
+        ("yeet a[+1]", "yeet a[1]"),
+        ("yeet -2*+3", "yeet -6"),
+        ("yeet 2++3", "yeet 5"),
+    ]
+    .iter()
+    .all(|&(line, folded)| {
+        Instruction::try_from(line).map(|instruction| instruction.to_string())
+            == Ok(folded.to_string())
+    });
+    failed |= !ok;
+    println!("signed constants: {}", if ok { "ok" } else { "broken" });
+    // sorting would make tasks 3 and 4 trivial
+    let sorts = |task| {
+        Program::compile(&split("sort it out a"))
+            .and_then(|program| program.check_banned(task))
+            .is_ok()
+    };
+    let ok = sorts(1) && !sorts(3) && !sorts(4);
+    failed |= !ok;
+    println!("banned sorting: {}", if ok { "ok" } else { "broken" });
+    // so would the max of x and -x in task 2
+    let ok = Program::compile(&split("goat m between x and m"))
+        .and_then(|program| program.check_banned(2))
+        .is_err();
+    failed |= !ok;
+    println!("banned goat: {}", if ok { "ok" } else { "broken" });
+    // a jump to a line that doesn't exist gets a hint in practice mode
+    let mut program = Program::compile(&split("bruh t is lowkey just 99\nsimp for t"))
+        .map_err(|verdict| CheckerFail(verdict.to_string()))?;
+    let ok = program
+        .execute(1000)
+        .err()
+        .and_then(|verdict| program.hint(&verdict))
+        .is_some();
+    failed |= !ok;
+    println!(
+        "hint for jumping nowhere: {}",
+        if ok { "ok" } else { "broken" }
+    );
+    // n is at least 1 in the array tasks, so an empty array can't sneak in
+    // as a corner case file
+    for (task, text) in [(3, "0\n\n"), (4, "0\n\n1\n")] {
+        let format = test_plan(task, DEFAULT_SEED)?.format;
+        let ok = format
+            .read(text)
+            .is_ok_and(|input| format.validate(&input).is_err());
+        failed |= !ok;
+        println!(
+            "empty array rejected in task {}: {}",
+            task,
+            if ok { "ok" } else { "broken" }
+        );
+    }
+    for fixture in cost_corpus() {
+        let runtime = fixture.run()?.runtime();
+        failed |= runtime != fixture.runtime;
+        println!(
+            "cost of {}: {} (expected {})",
+            fixture.name, runtime, fixture.runtime
+        );
+    }
+    for (index, lesson) in LESSONS.iter().enumerate() {
+        let lines = lesson
+            .solution
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let verdict = lesson.check(&lines);
+        failed |= !matches!(verdict, Verdict::Correct());
+        println!("lesson {}: {} {}", index + 1, verdict.code(), verdict);
+    }
+    for doc in VERDICT_DOCS.iter().filter(|doc| !doc.example.is_empty()) {
+        let tasks = doc.tasks()?;
+        failed |= tasks.is_empty();
+        println!(
+            "example for {} {}: {}",
+            doc.code,
+            doc.message,
+            if tasks.is_empty() {
+                "out of date"
+            } else {
+                "ok"
+            }
+        );
+    }
+    for doc in INSTRUCTION_DOCS {
+        let line = doc.example.to_string();
+        let printed = Instruction::try_from(doc.example).map(|instruction| instruction.to_string());
+        let cost = Program::compile(std::slice::from_ref(&line))
+            .map(|program| program.code.costs[0])
+            .ok();
+        let ok = printed.as_ref() == Ok(&line)
+            && cost == Some(Program::line_cost(&line) + doc.extra_cost)
+            && InstructionDoc::lookup(&line)
+                .iter()
+                .any(|found| std::ptr::eq(*found, doc));
+        failed |= !ok;
+        println!(
+            "docs for `{}`: {}",
+            doc.pattern,
+            if ok { "ok" } else { "out of date" }
+        );
+    }
+    // none of the checker's commands need every knob of the embedding api, so
+    // it's driven here the way an embedder would
+    let lines = [
+        "bruh a is lowkey just 21",
+        "double a",
+        "yeet a",
+        "go touch some grass",
+    ]
+    .map(String::from);
+    let observed = Rc::new(std::cell::Cell::new(0));
+    let counter = observed.clone();
+    let builder = |memory_limit| {
+        InterpreterBuilder::new()
+            .memory_limit(memory_limit)
+            .cost_model(|_| 10)
+            .opcode("double _", 1, |program, operands| {
+                *program.get_reference_mut(&operands[0])? *= 2;
+                Ok(())
+            })
+    };
+    let mut interpreter = builder(usize::MAX)
+        .observer(move |_| counter.set(counter.get() + 1))
+        .build(&lines)
+        .map_err(|verdict| CheckerFail(verdict.to_string()))?;
+    let ok = interpreter.execute().is_ok()
+        && interpreter.program().output == [Variable::Integer(42)]
+        && interpreter.program().runtime == 4 * 10 + 1
+        && observed.get() == 4
+        && builder(0)
+            .build(&lines)
+            .is_ok_and(|mut interpreter| interpreter.execute().is_err());
+    // building over and over reuses the ids of opcodes nothing uses anymore
+    let registered = || {
+        OPCODES
+            .get()
+            .map_or(0, |opcodes| opcodes.lock().unwrap().len())
+    };
+    let before = registered();
+    for _ in 0..10 {
+        drop(builder(usize::MAX).build(&lines));
+    }
+    let ok = ok && registered() == before;
+    failed |= !ok;
+    println!("embedding api: {}", if ok { "ok" } else { "broken" });
+    // the packages are built somewhere else, so the checker in them has to
+    // compile with nothing around it. only checked when there's a rustc
+    let dir = std::env::temp_dir().join(format!("based-selftest-{}", std::process::id()));
+    let out = dir.to_string_lossy().to_string();
+    export_polygon(&["--task", "1", "--out", &out].map(String::from))?;
+    let checked = std::process::Command::new("rustc")
+        .args(["--emit=metadata", "--out-dir", &out])
+        .arg(dir.join("files/check.rs"))
+        .stderr(std::process::Stdio::null())
+        .status();
+    std::fs::remove_dir_all(&dir)?;
+    match checked {
+        Ok(status) => {
+            failed |= !status.success();
+            println!(
+                "exported checker: {}",
+                if status.success() {
+                    "ok"
+                } else {
+                    "doesn't compile"
+                }
+            );
+        }
+        Err(_) => println!("exported checker: skipped, no rustc"),
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// prints what a verdict means, or what an instruction looks like and does
+fn explain(args: &[String]) -> Result<(), CheckerFail> {
+    let query = args.join(" ");
+    let docs = VerdictDoc::lookup(&query)
+        .iter()
+        .map(|doc| doc.to_string())
+        .chain(
+            InstructionDoc::lookup(&query)
+                .iter()
+                .map(|doc| doc.to_string()),
+        )
+        .collect::<Vec<_>>();
+    if docs.is_empty() {
+        return Err(CheckerFail(format!(
+            "no idea what '{}' is",
+            compress(&query)
+        )));
+    }
+    println!("{}", docs.join("\n\n"));
+    Ok(())
+}
+
+// a known program, for tools that need programs to start from, like fuzzers,
+// tutorials and editors, so they don't each collect their own
+struct CorpusEntry {
+    // `instructions` for one line of each instruction, `solutions` for the
+    // jury's, and the verdict code for programs that get that verdict
+    category: String,
+    name: String,
+    source: String,
+    // what judging it gives, and on which tasks; instructions aren't judged
+    verdict: Option<(&'static str, &'static str)>,
+    tasks: Vec<i32>,
+}
+
+fn corpus() -> Result<Vec<CorpusEntry>, CheckerFail> {
+    let mut entries = INSTRUCTION_DOCS
+        .iter()
+        .map(|doc| CorpusEntry {
+            category: "instructions".to_string(),
+            name: slug(doc.pattern),
+            source: doc.example.to_string(),
+            verdict: None,
+            tasks: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    for task in TASKS {
+        entries.push(CorpusEntry {
+            category: "solutions".to_string(),
+            name: format!("task-{}", task),
+            source: jury_solution(task)?,
+            verdict: Some(("AC", "")),
+            tasks: vec![task],
+        });
+    }
+    for doc in VERDICT_DOCS.iter().filter(|doc| !doc.example.is_empty()) {
+        entries.push(CorpusEntry {
+            category: doc.code.to_lowercase(),
+            name: Some(slug(doc.message))
+                .filter(|name| !name.is_empty())
+                .unwrap_or("any".to_string()),
+            source: doc.example.to_string(),
+            verdict: Some((doc.code, doc.message)),
+            tasks: doc.tasks()?,
+        });
+    }
+    Ok(entries)
+}
+
+// writes the corpus as <category>/<name>.based files, with an index of what
+// each one gets
+fn write_corpus(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let out = std::path::Path::new(options.require("out")?);
+    let entries = corpus()?;
+    let mut index = String::new();
+    for entry in &entries {
+        let path = format!("{}/{}.based", entry.category, entry.name);
+        std::fs::create_dir_all(out.join(&entry.category))?;
+        std::fs::write(out.join(&path), format!("{}\n", entry.source))?;
+        index += &match &entry.verdict {
+            Some((code, message)) => format!(
+                "{} {} tasks={} {}\n",
+                path,
+                code,
+                entry
+                    .tasks
+                    .iter()
+                    .map(i32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+                message
+            ),
+            None => format!("{} compiles\n", path),
+        };
+    }
+    std::fs::write(out.join("index.txt"), index)?;
+    println!("wrote {} programs to {}", entries.len(), out.display());
+    Ok(())
+}
+
+// one step of `learn`: the instructions it introduces, something to write
+// with them, and the tests the answer has to pass
+struct Lesson {
+    keywords: &'static [&'static str],
+    exercise: &'static str,
+    tests: &'static [(&'static [i64], i64)],
+    solution: &'static str,
+}
+
+const LESSONS: &[Lesson] = &[
+    Lesson {
+        keywords: &["yoink", "yeet", "go touch some grass"],
+        exercise: "read a number and print it back",
+        tests: &[(&[5], 5), (&[-3], -3), (&[0], 0)],
+        solution: "yoink a\nyeet a\ngo touch some grass",
+    },
+    Lesson {
+        keywords: &["*slaps", "rip this"],
+        exercise: "read a number and print it plus 1",
+        tests: &[(&[5], 6), (&[-1], 0), (&[0], 1)],
+        solution: "yoink a\n*slaps 1 on top of a*\nyeet a\ngo touch some grass",
+    },
+    Lesson {
+        keywords: &["bruh"],
+        exercise: "read a number and print it times two",
+        tests: &[(&[5], 10), (&[-4], -8), (&[0], 0)],
+        solution: "yoink a\nbruh b is lowkey just a + a\nyeet b\ngo touch some grass",
+    },
+    Lesson {
+        keywords: &["vibe check", "simp for"],
+        exercise: "read a number and print 1 if it's positive, 0 otherwise",
+        tests: &[(&[5], 1), (&[0], 0), (&[-3], 0)],
+        solution: "yoink a\nvibe check a ratios 0\nsimp for 6\nyeet 0\ngo touch some grass\nyeet 1\ngo touch some grass",
+    },
+];
+
+impl Lesson {
+    const TIME_LIMIT: usize = 10000;
+    fn check(&self, lines: &[String]) -> Verdict {
+        let code = match Program::compile(lines) {
+            Ok(program) => program.code,
+            Err(compile_error) => return compile_error,
+        };
+        for (input, answer) in self.tests {
+            let test = TestCase {
+                params: format!("input {:?}", input),
+                label: None,
+                input: input
+                    .iter()
+                    .map(|&value| Variable::Integer(value))
+                    .collect(),
+                answer: Variable::Integer(*answer),
+            };
+            let verdict = test.run_and_check(&mut Program::spawn(code.clone()), Self::TIME_LIMIT);
+            if let Verdict::WrongAnswer(message) = verdict {
+                return Verdict::WrongAnswer(format!("{} on {}", message, test.params));
+            } else if !matches!(verdict, Verdict::Correct()) {
+                return verdict;
+            }
+        }
+        Verdict::Correct()
+    }
+}
+
+// lines typed up to the first empty one, None once stdin runs out
+fn read_answer(
+    input: &mut impl Iterator<Item = std::io::Result<String>>,
+) -> Result<Option<Vec<String>>, CheckerFail> {
+    println!("(type ur program and then an empty line, or `show me` for an answer)");
+    let mut lines = Vec::new();
+    for line in input {
+        let line = line?;
+        if !line.trim().is_empty() {
+            lines.push(line);
+        } else if !lines.is_empty() {
+            return Ok(Some(lines));
+        }
+    }
+    Ok(None)
+}
+
+// a few small exercises that go through the instructions one by one, and
+// then task 1 for real
+fn learn(_: &[String]) -> Result<(), CheckerFail> {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock().lines();
+    println!("welcome to based, u will write a few tiny programs and they get judged right here");
+    let total = LESSONS.len() + 1;
+    for (index, lesson) in LESSONS.iter().enumerate() {
+        println!("\nlesson {} of {}\n", index + 1, total);
+        for keyword in lesson.keywords {
+            for doc in InstructionDoc::lookup(keyword) {
+                println!("{}\n", doc);
+            }
+        }
+        println!("now {}", lesson.exercise);
+        loop {
+            let Some(lines) = read_answer(&mut input)? else {
+                return Ok(());
+            };
+            if lines == ["show me"] {
+                println!("{}\n", lesson.solution);
+                continue;
+            }
+            let verdict = lesson.check(&lines);
+            println!("{}", verdict);
+            if matches!(verdict, Verdict::Correct()) {
+                break;
+            }
+        }
+    }
+    println!("\nlesson {} of {}\n", total, total);
+    println!("that's all of them, now read two numbers and print their sum, judged like task 1");
+    loop {
+        let Some(lines) = read_answer(&mut input)? else {
+            return Ok(());
+        };
+        if lines == ["show me"] {
+            println!("nah, this one's on u\n");
+            continue;
+        }
+        let verdict = judge(1, &lines)?;
+        println!("{}", verdict);
+        if matches!(verdict, Verdict::Correct()) {
+            println!("ur ready for tasks 2 to {}", TASKS.len());
+            return Ok(());
+        }
+    }
+}
+
+// patches a skeleton for the next task into the checker source, right before
+// each of the catch-all match arms that a new task has to be added to
+fn new_task(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let name = options.require("name")?;
+    let path = std::path::Path::new(options.get("source").unwrap_or("checker.rs"));
+    let mut source = std::fs::read_to_string(path)?;
+    let task = TASKS.len() as i32 + 1;
+    if source.contains(&format!("struct Task{}(", task)) {
+        return Err(CheckerFail(format!(
+            "task {} is already in the source, rebuild the checker first",
+            task
+        )));
+    }
+    let patches = [
+        (
+            "// a test for an input that was read from somewhere else",
+            format!(
+                "// {name}\n\
+                struct Task{task}();\n\
+                \n\
+                impl Task{task} {{\n    \
+                    fn test_case(a: Vec<i64>) -> TestCase {{\n        \
+                        // TODO: compute the answer\n        \
+                        let answer = Variable::Integer(0);\n        \
+                        TestCase {{\n            \
+                            params: format!(\"n={{}}\", a.len()),\n            \
+                            label: None,\n            \
+                            input: vec![Variable::Integer(a.len() as i64), Variable::Array(a)],\n            \
+                            answer,\n        \
+                        }}\n    \
+                    }}\n\
+                }}\n\
+                \n",
+                name = name,
+                task = task
+            ),
+        ),
+        (
+            "        _ => Err(CheckerFail(format!(\"input doesn't fit task {}\", task))),",
+            format!(
+                "        ({task}, [Integer(_), Array(a)]) => Ok(Task{task}::test_case(a.clone())),\n",
+                task = task
+            ),
+        ),
+        (
+            "        _ => Vec::new(),",
+            format!(
+                "        // TODO: add corner cases\n        \
+                {task} => vec![Task{task}::test_case(vec![0])],\n",
+                task = task
+            ),
+        ),
+        (
+            "        _ => {\n            return Err(CheckerFail(format!(\"unknown task id {}\", task)));",
+            format!(
+                "        {task} => {{\n            \
+                    for n in 1..=50 {{\n                \
+                        pinned.push(vec![(\"n\", n)]);\n            \
+                    }}\n            \
+                    (100000, \"int n with 1<=n<=50, array[n] a with -2^59<=a<2^59\")\n        \
+                }}\n",
+                task = task
+            ),
+        ),
+    ];
+    for (anchor, patch) in patches {
+        let Some(position) = source.find(anchor) else {
+            return Err(CheckerFail(format!(
+                "couldn't find `{}` in the source",
+                anchor
+            )));
+        };
+        source.insert_str(position, &patch);
+    }
+    let tasks = format!("const TASKS: [i32; {}] = {:?};", TASKS.len(), TASKS);
+    let mut next_tasks = TASKS.to_vec();
+    next_tasks.push(task);
+    source = source.replace(
+        &tasks,
+        &format!(
+            "const TASKS: [i32; {}] = {:?};",
+            next_tasks.len(),
+            next_tasks
+        ),
+    );
+    let solution = path.with_file_name(format!("solutions/{}-ac", task));
+    std::fs::write(path, source)?;
+    std::fs::write(&solution, "yoink n\nyoink a\nyeet 0\ngo touch some grass\n")?;
+    println!("added task {} ({}) to {}", task, name, path.display());
+    println!("wrote a stub jury solution to {}", solution.display());
+    Ok(())
+}
+
+// proposes time limits from what the jury's solutions cost over a bunch of
+// seeds, and with --write puts them into the test plans in the source
+fn limits(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &["write"])?;
+    let factor = options.get("factor").unwrap_or("2").parse::<f64>()?;
+    let seeds = options.get("seeds").unwrap_or("10").parse::<u128>()?;
+    let solutions = std::path::Path::new(options.get("solutions").unwrap_or("solutions"));
+    let path = std::path::Path::new(options.get("source").unwrap_or("checker.rs"));
+    let tasks = match options.get("task") {
+        Some(task) => vec![task.parse()?],
+        None => TASKS.to_vec(),
+    };
+    let mut source = String::new();
+    if options.has("write") {
+        source = std::fs::read_to_string(path)?;
+    }
+    for task in tasks {
+        // the jury's solution, and any other accepted ones lying around
+        let mut programs = vec![jury_solution(task)?.lines().map(String::from).collect()];
+        if let Ok(entries) = std::fs::read_dir(solutions) {
+            let mut paths = entries
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<_>, CheckerFail>>()?;
+            paths.sort();
+            for path in paths {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let lines = match name.starts_with(&format!("{}-ac", task)) {
+                    true => read_lines(&path.to_string_lossy())?,
+                    false => continue,
+                };
+                if !programs.contains(&lines) {
+                    programs.push(lines);
+                }
+            }
+        }
+        // a jury's solution far past the current limit is broken rather than
+        // slow, and one that never stops would hang here
+        const LIMIT_MULTIPLE: usize = 10;
+        let mut worst = 0;
+        let mut current = 0;
+        for seed in 0..seeds {
+            let plan = test_plan(task, DEFAULT_SEED.wrapping_add(seed))?;
+            current = plan.time_limit;
+            for lines in &programs {
+                let mut program = Program::compile(lines).map_err(|verdict| {
+                    CheckerFail(format!("jury's solution failed with verdict {:?}", verdict))
+                })?;
+                program.strip_for_judging();
+                let pristine = program.snapshot();
+                for (_, test) in plan.tests() {
+                    program.restore(&pristine);
+                    let verdict = test.run_and_check(
+                        &mut program,
+                        plan.time_limit.saturating_mul(LIMIT_MULTIPLE),
+                    );
+                    if !matches!(verdict, Verdict::Correct()) {
+                        return Err(CheckerFail(format!(
+                            "jury's solution failed on {} with verdict {:?}",
+                            test.describe(),
+                            verdict
+                        )));
+                    }
+                    worst = worst.max(program.runtime());
+                }
+            }
+        }
+        // rounded up to two significant digits
+        let proposed = (worst as f64 * factor).ceil() as usize;
+        let unit = 10usize.pow((proposed.max(1) as f64).log10() as u32).max(10) / 10;
+        let proposed = (proposed + unit - 1) / unit * unit;
+        println!(
+            "task {}: jury's worst cost {} over {} seeds and {} solutions, limit {}, proposed {}",
+            task,
+            worst,
+            seeds,
+            programs.len(),
+            current,
+            proposed
+        );
+        if options.has("write") && proposed != current {
+            source = replace_time_limit(&source, task, current, proposed)?;
+        }
+    }
+    if options.has("write") {
+        std::fs::write(path, source)?;
+        println!(
+            "wrote the limits to {}, rebuild the checker",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+// the limit is the first number in the task's arm of `test_plan`
+fn replace_time_limit(
+    source: &str,
+    task: i32,
+    current: usize,
+    proposed: usize,
+) -> Result<String, CheckerFail> {
+    let not_found = || {
+        CheckerFail(format!(
+            "couldn't find the limit of task {} in the source",
+            task
+        ))
+    };
+    let plans = source.find("fn test_plan(").ok_or_else(not_found)?;
+    let arm = plans
+        + source[plans..]
+            .find(&format!("        {} => {{\n", task))
+            .ok_or_else(not_found)?;
+    let end = arm + source[arm..].find("\n        }\n").ok_or_else(not_found)?;
+    let limit = arm
+        + source[arm..end]
+            .find(&format!("{},", current))
+            .ok_or_else(not_found)?;
+    let mut source = source.to_string();
+    source.replace_range(
+        limit..limit + current.to_string().len(),
+        &proposed.to_string(),
+    );
+    Ok(source)
+}
+
+// packages build the checker from its source, which is `--source` or the
+// checker.rs next to where this runs
+fn write_checker_source(
+    options: &Options,
+    dir: &std::path::Path,
+    name: &str,
+) -> Result<(), CheckerFail> {
+    let source = options.get("source").unwrap_or("checker.rs");
+    std::fs::copy(source, dir.join(name))?;
+    Ok(())
+}
+
+// on polygon, the input of the only test is the task id, the answer is the
+// jury's solution, and this checker does the actual testing
+fn export_polygon(args: &[String]) -> Result<(), CheckerFail> {
+    const WALL_TIME_LIMIT_MS: usize = 1000;
+    const MEMORY_LIMIT_BYTES: usize = 256 << 20;
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let out = std::path::Path::new(options.require("out")?);
+    let plan = test_plan(task, DEFAULT_SEED)?;
+    let solution = jury_solution(task)?;
+    std::fs::create_dir_all(out.join("tests"))?;
+    std::fs::create_dir_all(out.join("files"))?;
+    std::fs::write(out.join("tests/01"), format!("{}\n", task))?;
+    std::fs::write(out.join("tests/01.a"), solution)?;
+    write_checker_source(&options, &out.join("files"), "check.rs")?;
+    std::fs::write(
+        out.join("files/validator.cpp"),
+        format!(
+            r#"#include "testlib.h"
+
+int main(int argc, char *argv[]) {{
+    registerValidation(argc, argv);
+    inf.readInt({task}, {task}, "task");
+    inf.readEoln();
+    inf.readEof();
+}}
+"#,
+            task = task
+        ),
+    )?;
+    std::fs::write(
+        out.join("problem.xml"),
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8" standalone="no"?>
+<problem short-name="based-{task}">
+    <!-- cost limit {cost_limit} per test, {tests} tests run by the checker -->
+    <judging input-file="" output-file="">
+        <testset name="tests">
+            <time-limit>{time_limit}</time-limit>
+            <memory-limit>{memory_limit}</memory-limit>
+            <test-count>1</test-count>
+            <input-path-pattern>tests/%02d</input-path-pattern>
+            <answer-path-pattern>tests/%02d.a</answer-path-pattern>
+            <tests>
+                <test method="manual" sample="true"/>
+            </tests>
+        </testset>
+    </judging>
+    <assets>
+        <checker type="testlib">
+            <source path="files/check.rs" type="rust"/>
+        </checker>
+        <validators>
+            <validator>
+                <source path="files/validator.cpp" type="cpp.g++17"/>
+            </validator>
+        </validators>
+    </assets>
+</problem>
+"#,
+            task = task,
+            cost_limit = plan.time_limit,
+            tests = plan.tests().count(),
+            time_limit = WALL_TIME_LIMIT_MS,
+            memory_limit = MEMORY_LIMIT_BYTES,
+        ),
+    )?;
+    println!(
+        "wrote polygon package for task {} to {}",
+        task,
+        out.display()
+    );
+    Ok(())
+}
+
+// every file under a directory and its subdirectories, sorted
+fn list_files(dir: &std::path::Path) -> Result<Vec<std::path::PathBuf>, CheckerFail> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        match entry.file_type()?.is_dir() {
+            true => files.extend(list_files(&entry.path())?),
+            false => files.push(entry.path()),
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// turns the tests of another judge's package into a test pack, so a problem
+// can move onto this checker without rewriting its tests. inputs are read
+// with the task's input format and answered by the checker itself
+fn import(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let out = options.require("out")?;
+    let [dir] = &options.positional[..] else {
+        return Err(CheckerFail(
+            "expected exactly one package directory".to_string(),
+        ));
+    };
+    let dir = std::path::Path::new(dir);
+    let has_extension = |path: &std::path::PathBuf, extension: &str| {
+        path.extension().is_some_and(|found| found == extension)
+    };
+    let (samples, tests) = match options.require("from")? {
+        // tests/01, tests/02, ... next to their answers in tests/01.a
+        "polygon" => (
+            Vec::new(),
+            list_files(&dir.join("tests"))?
+                .into_iter()
+                .filter(|path| path.extension().is_none())
+                .collect(),
+        ),
+        // data/sample/*.in and data/secret/**/*.in
+        "kattis" => (
+            list_files(&dir.join("data/sample"))?
+                .into_iter()
+                .filter(|path| has_extension(path, "in"))
+                .collect(),
+            list_files(&dir.join("data/secret"))?
+                .into_iter()
+                .filter(|path| has_extension(path, "in"))
+                .collect::<Vec<_>>(),
+        ),
+        other => {
+            return Err(CheckerFail(format!(
+                "unknown package format {}, expected polygon or kattis",
+                compress(other)
+            )))
+        }
+    };
+    let plan = test_plan(task, DEFAULT_SEED)?;
+    let read = |paths: Vec<std::path::PathBuf>| {
+        paths
+            .iter()
+            .map(|path| {
+                let label = path.strip_prefix(dir).unwrap_or(path).display().to_string();
+                Ok(read_test(task, &plan.format, &path.to_string_lossy())?.labeled(&label))
+            })
+            .collect::<Result<Vec<_>, CheckerFail>>()
+    };
+    let corner_cases = read(samples)?;
+    let random = read(tests)?;
+    if corner_cases.is_empty() && random.is_empty() {
+        return Err(CheckerFail(format!("no tests in {}", dir.display())));
+    }
+    let imported = TestPlan {
+        corner_cases,
+        random,
+        ..plan
+    };
+    std::fs::write(out, imported.write_pack(task)?)?;
+    println!(
+        "imported {} tests for task {} into {}",
+        imported.tests().count(),
+        task,
+        out
+    );
+    Ok(())
+}
+
+// writes the inputs of a task's tests to files next to their answers, in the
+// tests/01 and tests/01.a layout that `import --from polygon` reads back
+fn export_tests(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let out = std::path::Path::new(options.require("out")?);
+    let plan = load_test_plan(task, options.get("pack"))?;
+    let tests = plan.select(&TestFilter::from_options(&options))?;
+    // wide enough that the files sort in test order
+    let width = plan.tests().count().to_string().len().max(2);
+    std::fs::create_dir_all(out)?;
+    for (id, test) in &tests {
+        let name = format!("{:0width$}", id, width = width);
+        std::fs::write(out.join(&name), format!("{}\n", input_text(&test.input)))?;
+        std::fs::write(out.join(name + ".a"), format!("{}\n", test.answer))?;
+    }
+    println!(
+        "wrote {} tests for task {} to {}",
+        tests.len(),
+        task,
+        out.display()
+    );
+    Ok(())
+}
+
+// the output validator interface used by kattis and icpc judges
+fn kattis(args: &[String]) -> Result<(), CheckerFail> {
+    const ACCEPTED: i32 = 42;
+    const WRONG_ANSWER: i32 = 43;
+    let [input, answer, feedback_dir, ..] = args else {
+        return Err(CheckerFail(
+            "expected input file, answer file and feedback directory".to_string(),
+        ));
+    };
+    let outcome = run_checker(
+        CheckerConfig::from_env(input, "-", answer),
+        RealFiles,
+        SystemClock,
+    );
+    let verdict = outcome.verdict?;
+    std::fs::write(
+        std::path::Path::new(feedback_dir).join("judgemessage.txt"),
+        format!("{}\n", verdict),
+    )?;
+    match verdict {
+        Verdict::Correct() => std::process::exit(ACCEPTED),
+        _ => std::process::exit(WRONG_ANSWER),
+    }
+}
+
+fn export_icpc(args: &[String]) -> Result<(), CheckerFail> {
+    const TIME_LIMIT_SECONDS: usize = 1;
+    const MEMORY_LIMIT_MIB: usize = 256;
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let out = std::path::Path::new(options.require("out")?);
+    let plan = test_plan(task, DEFAULT_SEED)?;
+    let validator = out.join("output_validators/checker");
+    std::fs::create_dir_all(out.join("data/secret"))?;
+    std::fs::create_dir_all(&validator)?;
+    std::fs::write(out.join("data/secret/01.in"), format!("{}\n", task))?;
+    std::fs::write(out.join("data/secret/01.ans"), jury_solution(task)?)?;
+    write_checker_source(&options, &validator, "checker.rs")?;
+    std::fs::write(
+        validator.join("build"),
+        "#!/bin/sh\nrustc -O checker.rs -o checker\n",
+    )?;
+    std::fs::write(
+        validator.join("run"),
+        "#!/bin/sh\nexec \"$(dirname \"$0\")/checker\" kattis \"$@\"\n",
+    )?;
+    #[cfg(unix)]
+    for script in ["build", "run"] {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(
+            validator.join(script),
+            std::fs::Permissions::from_mode(0o755),
+        )?;
+    }
+    std::fs::write(
+        out.join("problem.yaml"),
+        format!(
+            "name: task {task}\n\
+            validation: custom\n\
+            # cost limit {cost_limit} per test, {tests} tests run by the output validator\n\
+            limits:\n  time_limit: {time_limit}\n  memory: {memory_limit}\n",
+            task = task,
+            cost_limit = plan.time_limit,
+            tests = plan.tests().count(),
+            time_limit = TIME_LIMIT_SECONDS,
+            memory_limit = MEMORY_LIMIT_MIB,
+        ),
+    )?;
+    println!("wrote icpc package for task {} to {}", task, out.display());
+    Ok(())
+}
+
+// `checker <input> <output> <answer>`, the way judges run it
+fn work(argv: &[String]) -> CheckerOutcome {
+    let config = CheckerConfig::from_env(&argv[1], &argv[2], &argv[3]);
+    run_checker(config, RealFiles, SystemClock)
+}
+
+// where the checker gets its files from, so that judging can also run on
+// files that only exist in memory
+trait FileProvider {
+    fn read(&self, path: &str) -> Result<Vec<u8>, CheckerFail>;
+    fn append(&self, path: &str, text: &str) -> Result<(), CheckerFail>;
+}
+
+// the filesystem, with `-` for stdin
+struct RealFiles;
+
+impl FileProvider for RealFiles {
+    fn read(&self, path: &str) -> Result<Vec<u8>, CheckerFail> {
+        use std::io::Read;
+        let mut bytes = Vec::new();
+        match path {
+            "-" => std::io::stdin().lock().read_to_end(&mut bytes)?,
+            path => std::fs::File::open(path)?.read_to_end(&mut bytes)?,
+        };
+        Ok(bytes)
+    }
+    fn append(&self, path: &str, text: &str) -> Result<(), CheckerFail> {
+        use std::io::Write;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(text.as_bytes())?;
+        Ok(())
+    }
+}
+
+// the time since the unix epoch, for timestamps and timing the judging
+trait Clock {
+    fn now(&self) -> std::time::Duration;
+}
+
+struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> std::time::Duration {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
+
+// everything about judging one submission that comes from the command line
+// or the environment
+struct CheckerConfig {
+    input: String,
+    output: String,
+    answer: String,
+    pack: Option<String>,
+    precedence: Option<String>,
+    audit_log: Option<String>,
+}
+
+impl CheckerConfig {
+    // the task's own plan, the first failure and no audit log
+    fn new(input: &str, output: &str, answer: &str) -> CheckerConfig {
+        CheckerConfig {
+            input: input.to_string(),
+            output: output.to_string(),
+            answer: answer.to_string(),
+            pack: None,
+            precedence: None,
+            audit_log: None,
+        }
+    }
+    fn from_env(input: &str, output: &str, answer: &str) -> CheckerConfig {
+        CheckerConfig {
+            pack: std::env::var(TEST_PACK_VARIABLE).ok(),
+            precedence: std::env::var(PRECEDENCE_VARIABLE).ok(),
+            audit_log: std::env::var(AUDIT_LOG_VARIABLE).ok(),
+            ..CheckerConfig::new(input, output, answer)
+        }
+    }
+}
+
+struct CheckerOutcome {
+    verdict: Result<Verdict, CheckerFail>,
+    stderr: String,
+    exit_code: i32,
+}
+
+// judges a submission without touching anything but `files` and `clock`,
+// the command line and the embedded harness are thin shells around this
+fn run_checker(
+    config: CheckerConfig,
+    files: impl FileProvider,
+    clock: impl Clock,
+) -> CheckerOutcome {
+    let mut stderr = String::new();
+    let verdict = check(&config, &files, &clock, &mut stderr);
+    let (message, exit_code) = report(&verdict);
+    stderr += &format!("{}\n", message);
+    CheckerOutcome {
+        verdict,
+        stderr,
+        exit_code,
+    }
+}
+
+// the jury's solution has to pass before anyone else is judged
+fn check_jury(
+    plan: &TestPlan,
+    precedence: &VerdictPrecedence,
+    task: i32,
+    jury: &[String],
+) -> Result<(), CheckerFail> {
+    match judge_on(plan, precedence, task, jury) {
+        Verdict::Correct() => Ok(()),
+        verdict => Err(CheckerFail(format!(
+            "jury's solution failed with verdict {:?}",
+            verdict
+        ))),
+    }
+}
+
+fn judge_participant(
+    plan: &TestPlan,
+    precedence: &VerdictPrecedence,
+    task: i32,
+    participant: &Result<Vec<String>, CheckerFail>,
+) -> Verdict {
+    match participant {
+        Ok(lines) => judge_on(plan, precedence, task, lines),
+        Err(error) => Verdict::OtherError(error.0.clone()),
+    }
+}
+
+// what the checker prints to stderr at the end and the code it exits with
+fn report(result: &Result<Verdict, CheckerFail>) -> (String, i32) {
+    match result {
+        Err(CheckerFail(message)) => (
+            format!("CHECKER ERROR author made the oopsie: {}", message),
+            3,
+        ),
+        Ok(verdict @ Verdict::Correct()) => (verdict.to_string(), 0),
+        Ok(verdict) => (verdict.to_string(), 1),
+    }
+}
+
+// problems that don't change the verdict go to `stderr`
+fn check(
+    config: &CheckerConfig,
+    files: &impl FileProvider,
+    clock: &impl Clock,
+    stderr: &mut String,
+) -> Result<Verdict, CheckerFail> {
+    let text = |path: &str| Ok::<_, CheckerFail>(String::from_utf8(files.read(path)?)?);
+    let lines = |path: &str| -> Result<Vec<String>, CheckerFail> {
+        Ok(text(path)?.lines().map(String::from).collect())
+    };
+    let task = text(&config.input)?.trim().parse()?;
+    let plan = match &config.pack {
+        Some(path) => TestPlan::read_pack(&files.read(path)?, task)?,
+        None => test_plan(task, DEFAULT_SEED)?,
+    };
+    let precedence = match &config.precedence {
+        Some(text) => VerdictPrecedence::parse(text)?,
+        None => VerdictPrecedence::First,
+    };
+    check_jury(&plan, &precedence, task, &lines(&config.answer)?)?;
+    let participant = lines(&config.output);
+    let started = clock.now();
+    let verdict = judge_participant(&plan, &precedence, task, &participant);
+    if let Some(path) = &config.audit_log {
+        let lines = participant.as_deref().ok();
+        let finished = clock.now();
+        let record = audit_record(
+            task,
+            lines,
+            &verdict,
+            finished,
+            finished.saturating_sub(started),
+        );
+        if let Err(CheckerFail(message)) = files.append(path, &record) {
+            *stderr += &format!("couldn't write the audit log: {}\n", message);
+        }
+    }
+    Ok(verdict)
+}
+
+// judges run the checker with fixed arguments, so this has to be opted into
+// through the environment
+const AUDIT_LOG_VARIABLE: &str = "BASED_AUDIT_LOG";
+
+// bumped whenever a field of the json that score and the audit log write
+// changes meaning or goes away, so whatever reads it can tell. new fields
+// don't bump it
+const JSON_SCHEMA_VERSION: u32 = 1;
+
+fn json_string(string: &str) -> String {
+    let mut json = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// one json object per judged submission, appended to the audit log so that
+// appeals don't have to dig through stderr
+fn audit_record(
+    task: i32,
+    lines: Option<&[String]>,
+    verdict: &Verdict,
+    timestamp: std::time::Duration,
+    elapsed: std::time::Duration,
+) -> String {
+    let program = lines.and_then(|lines| Program::compile(lines).ok());
+    let author = program
+        .as_ref()
+        .and_then(|program| program.metadata().author.as_deref())
+        .map_or("null".to_string(), json_string);
+    let hash = program.as_ref().map_or("null".to_string(), |program| {
+        format!("\"{:016x}\"", program.semantic_hash())
+    });
+    format!(
+        "{{\"schema_version\":{},\"timestamp\":{},\"task\":{},\"author\":{},\"hash\":{},\"seed\":\"{:#x}\",\
+        \"language_version\":{},\"verdict\":\"{}\",\"message\":{},\"elapsed_ms\":{}}}\n",
+        JSON_SCHEMA_VERSION,
+        timestamp.as_secs(),
+        task,
+        author,
+        hash,
+        DEFAULT_SEED,
+        ProgramMetadata::LANGUAGE_VERSION,
+        verdict.code(),
+        json_string(verdict.message()),
+        elapsed.as_millis()
+    )
+}
+
+fn score(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &["practice", "json", "strict"])?;
+    let practice = options.has("practice");
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let program =
+        read_program(filename)?.and_then(|program| program.check_result(options.has("strict")));
+    let task = options.task(program.as_ref().ok())?;
+    let program = program
+        .and_then(|program| program.check_banned(task))
+        .map(|mut program| {
+            program.hide_test_data();
+            program
+        });
+    let plan = load_test_plan(task, options.get("pack"))?;
+    let precedence = match options.get("precedence") {
+        Some(text) => Some(VerdictPrecedence::parse(text)?),
+        None => None,
+    };
+    let artifacts = options.get("artifacts").map(std::path::Path::new);
+    let selected = plan.select(&TestFilter::from_options(&options))?;
+    if selected.len() < plan.tests().count() {
+        eprintln!(
+            "running {} of {} tests",
+            selected.len(),
+            plan.tests().count()
+        );
+    }
+    let results: Vec<(Verdict, Option<_>, Option<SequenceDiff>)> = match program {
+        Ok(program) => selected
+            .iter()
+            .map(|&(id, test)| {
+                let mut program = program.clone();
+                let mut check = test.output_check();
+                let verdict = test.run_with(&mut program, plan.time_limit, &mut check);
+                let hint = program.hint(&verdict).filter(|_| practice);
+                if let (Some(dir), false) = (artifacts, matches!(verdict, Verdict::Correct())) {
+                    save_artifact(dir, &program, id - 1, test, &verdict)?;
+                }
+                Ok((verdict, hint, check.diff))
+            })
+            .collect::<Result<_, CheckerFail>>()?,
+        Err(compile_error) => selected
+            .iter()
+            .map(|_| (compile_error.clone(), None, None))
+            .collect(),
+    };
+    if let Some(dir) = artifacts {
+        RetentionPolicy::from_options(&options)?.prune(dir)?;
+    }
+    let subtasks = subtasks(task);
+    let mut passed = Vec::<bool>::new();
+    let mut total = 0;
+    let mut json = Vec::new();
+    for (index, subtask) in subtasks.iter().enumerate() {
+        let failure = selected
+            .iter()
+            .zip(&results)
+            .filter(|((_, test), _)| test.size() <= subtask.max_n)
+            .find(|(_, (verdict, _, _))| !matches!(verdict, Verdict::Correct()));
+        let missing = subtask
+            .prerequisites
+            .iter()
+            .filter(|&&prerequisite| !passed[prerequisite])
+            .map(|prerequisite| (prerequisite + 1).to_string())
+            .collect::<Vec<_>>();
+        let points = if failure.is_none() && missing.is_empty() {
+            subtask.points
+        } else {
+            0
+        };
+        passed.push(points == subtask.points);
+        total += points;
+        if options.has("json") {
+            let failed = failure.map_or("null".to_string(), |((id, test), (verdict, _, diff))| {
+                format!(
+                    "{{\"test\":{},\"label\":{},\"verdict\":{},\"message\":{},\"diff\":{}}}",
+                    id,
+                    test.label
+                        .as_deref()
+                        .map_or("null".to_string(), json_string),
+                    json_string(verdict.code()),
+                    json_string(verdict.message()),
+                    diff.as_ref().map_or("null".to_string(), SequenceDiff::json)
+                )
+            });
+            json.push(format!(
+                "{{\"points\":{},\"max_points\":{},\"needs\":[{}],\"failed\":{}}}",
+                points,
+                subtask.points,
+                missing.join(","),
+                failed
+            ));
+            continue;
+        }
+        print!(
+            "subtask {}: {}/{} points",
+            index + 1,
+            points,
+            subtask.points
+        );
+        if let Some(((id, test), (verdict, hint, _))) = failure {
+            print!(", test #{} failed: {}", id, verdict);
+            if let Some(label) = &test.label {
+                print!(" ({})", label);
+            }
+            if let Some(hint) = hint {
+                print!(" (hint: {})", hint);
+            }
+        } else if !missing.is_empty() {
+            print!(", needs subtask {}", missing.join(", "));
+        }
+        println!();
+    }
+    let max_points = subtasks.iter().map(|subtask| subtask.points).sum::<u32>();
+    let headline = precedence
+        .map(|precedence| precedence.headline(results.iter().map(|(verdict, _, _)| verdict)));
+    if options.has("json") {
+        println!(
+            "{{\"schema_version\":{},\"points\":{},\"max_points\":{},\"subtasks\":[{}],\"verdict\":{}}}",
+            JSON_SCHEMA_VERSION,
+            total,
+            max_points,
+            json.join(","),
+            headline
+                .as_ref()
+                .map_or("null".to_string(), |verdict| json_string(verdict.code()))
+        );
+    } else {
+        println!("total: {}/{} points", total, max_points);
+        if let Some(verdict) = headline {
+            println!("verdict: {} {}", verdict.code(), verdict);
+        }
+    }
+    Ok(())
+}
+
+// how much of the failing tests saved by `score --artifacts` is kept around
+struct RetentionPolicy {
+    max_size: u64,
+    quota: u64,
+    ttl: std::time::Duration,
+}
+
+impl RetentionPolicy {
+    const DEFAULT_MAX_SIZE: u64 = 64 << 20;
+    const DEFAULT_QUOTA: u64 = 1 << 20;
+    const DEFAULT_TTL_HOURS: u64 = 7 * 24;
+    fn from_options(options: &Options) -> Result<Self, CheckerFail> {
+        let get = |name, default: u64| -> Result<u64, CheckerFail> {
+            match options.get(name) {
+                Some(value) => Ok(value.parse()?),
+                None => Ok(default),
+            }
+        };
+        Ok(RetentionPolicy {
+            max_size: get("max-size", Self::DEFAULT_MAX_SIZE)?,
+            quota: get("quota", Self::DEFAULT_QUOTA)?,
+            ttl: std::time::Duration::from_secs(get("ttl", Self::DEFAULT_TTL_HOURS)? * 60 * 60),
+        })
+    }
+    // drops expired artifacts, then the oldest ones of users over their
+    // quota, then the oldest ones overall until everything fits
+    fn prune(&self, dir: &std::path::Path) -> Result<(), CheckerFail> {
+        let now = std::time::SystemTime::now();
+        let mut artifacts = list_artifacts(dir, None)?;
+        let mut kept = Vec::new();
+        for (user, path, modified, size) in artifacts.drain(..) {
+            if now.duration_since(modified).unwrap_or_default() > self.ttl {
+                std::fs::remove_file(&path)?;
+            } else {
+                kept.push((user, path, size));
+            }
+        }
+        let mut used = HashMap::new();
+        for (user, _, size) in &kept {
+            *used.entry(user.clone()).or_insert(0) += size;
+        }
+        let mut total = used.values().sum::<u64>();
+        for (user, path, size) in kept {
+            let user_used = used.get_mut(&user).unwrap();
+            if *user_used > self.quota || total > self.max_size {
+                std::fs::remove_file(&path)?;
+                *user_used -= size;
+                total -= size;
+            }
+        }
+        Ok(())
+    }
+}
+
+// (user, path, modified, size) of every artifact, oldest first
+fn list_artifacts(
+    dir: &std::path::Path,
+    user: Option<&str>,
+) -> Result<Vec<(String, std::path::PathBuf, std::time::SystemTime, u64)>, CheckerFail> {
+    let mut artifacts = Vec::new();
+    if !dir.exists() {
+        return Ok(artifacts);
+    }
+    for user_dir in std::fs::read_dir(dir)? {
+        let user_dir = user_dir?;
+        let name = user_dir.file_name().to_string_lossy().into_owned();
+        if !user_dir.file_type()?.is_dir() || user.is_some_and(|user| user != name) {
+            continue;
+        }
+        for artifact in std::fs::read_dir(user_dir.path())? {
+            let artifact = artifact?;
+            let metadata = artifact.metadata()?;
+            artifacts.push((
+                name.clone(),
+                artifact.path(),
+                metadata.modified()?,
+                metadata.len(),
+            ));
+        }
+    }
+    artifacts.sort_by(|a, b| (a.2, &a.1).cmp(&(b.2, &b.1)));
+    Ok(artifacts)
+}
+
+// the canonical text form of an input, one line per variable and one per row
+// of a grid. arrays carry no length of their own, the task's input format
+// reads it from an earlier integer, so this is exactly what
+// `InputFormat::read` and `validate` accept
+fn input_text(input: &[Variable]) -> String {
+    input
+        .iter()
+        .map(|variable| match variable {
+            Variable::Integer(value) => value.to_string(),
+            Variable::Array(values) => values
+                .iter()
+                .map(i64::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
+            Variable::Text(text) => text.clone(),
+            Variable::Grid(rows) => rows
+                .iter()
+                .map(|row| row.iter().map(i64::to_string).collect::<Vec<_>>().join(" "))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn save_artifact(
+    dir: &std::path::Path,
+    program: &Program,
+    index: usize,
+    test: &TestCase,
+    verdict: &Verdict,
+) -> Result<(), CheckerFail> {
+    // keeps whatever the author wrote from escaping the directory
+    let user = program
+        .metadata()
+        .author
+        .as_deref()
+        .unwrap_or("anonymous")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .collect::<String>();
+    let user_dir = dir.join(if user.is_empty() { "anonymous" } else { &user });
+    std::fs::create_dir_all(&user_dir)?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    let mut text = format!(
+        "test #{}: {}\nverdict: {} {}\ninput:\n{}\n",
+        index + 1,
+        test.describe(),
+        verdict.code(),
+        verdict,
+        input_text(&test.input)
+    );
+    text += &format!(
+        "expected: {}\noutput: {}\n",
+        test.answer,
+        program
+            .output
+            .iter()
+            .map(Variable::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    std::fs::write(
+        user_dir.join(format!(
+            "{}-{:016x}-{}.txt",
+            timestamp,
+            program.semantic_hash(),
+            index + 1
+        )),
+        text,
+    )?;
+    Ok(())
+}
+
+fn artifacts(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &["prune"])?;
+    let [dir] = &options.positional[..] else {
+        return Err(CheckerFail(
+            "expected exactly one artifact directory".to_string(),
+        ));
+    };
+    let dir = std::path::Path::new(dir);
+    // listing never deletes anything unless asked to
+    if options.has("prune") {
+        RetentionPolicy::from_options(&options)?.prune(dir)?;
+    }
+    for (user, path, _, _) in list_artifacts(dir, options.get("user"))? {
+        println!("== {} {}", user, path.display());
+        print!("{}", std::fs::read_to_string(&path)?);
+    }
+    Ok(())
+}
+
+// aggregates over a directory of submissions, for editorials and for picking
+// limits of future tasks
+fn stats(args: &[String]) -> Result<(), CheckerFail> {
+    const BUCKETS: usize = 10;
+    const TOP_ERRORS: usize = 5;
+    let options = Options::parse(args, &[])?;
+    let task = options.require("task")?.parse()?;
+    let [dir] = &options.positional[..] else {
+        return Err(CheckerFail(
+            "expected exactly one submission directory".to_string(),
+        ));
+    };
+    // verdicts and costs both come from this plan
+    let plan = load_test_plan(task, options.get("pack"))?;
+    let precedence = VerdictPrecedence::from_env()?;
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>, CheckerFail>>()?;
+    paths.sort();
+    let mut verdicts = BTreeMap::new();
+    let mut histogram = [0; BUCKETS];
+    let mut compile_errors = HashMap::new();
+    let mut submissions = 0;
+    for path in paths.iter().filter(|path| path.is_file()) {
+        submissions += 1;
+        let lines = read_lines(&path.to_string_lossy())?;
+        let verdict = judge_on(&plan, &precedence, task, &lines);
+        *verdicts.entry(verdict.code()).or_insert(0) += 1;
+        match verdict {
+            Verdict::Correct() => {
+                let mut program = Program::compile(&lines).unwrap();
+                program.strip_for_judging();
+                let pristine = program.snapshot();
+                let mut worst = 0;
+                for (_, test) in plan.tests() {
+                    program.restore(&pristine);
+                    test.run_and_check(&mut program, plan.time_limit);
+                    worst = worst.max(program.runtime());
+                }
+                histogram[(worst * BUCKETS / (plan.time_limit + 1)).min(BUCKETS - 1)] += 1;
+            }
+            Verdict::CompileError(_, message) => {
+                *compile_errors.entry(message).or_insert(0) += 1;
+            }
+            _ => {}
+        }
+    }
+    println!("submissions: {}", submissions);
+    println!(
+        "verdicts: {}",
+        verdicts
+            .iter()
+            .map(|(code, count)| format!("{} {}", code, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    println!("worst test cost of accepted submissions:");
+    for (bucket, count) in histogram.iter().enumerate() {
+        println!(
+            "  {:>3}-{:>3}% of limit: {} {}",
+            bucket * 100 / BUCKETS,
+            (bucket + 1) * 100 / BUCKETS,
+            "#".repeat(*count),
+            count
+        );
+    }
+    let mut compile_errors = compile_errors.into_iter().collect::<Vec<_>>();
+    compile_errors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    println!("most common compile errors:");
+    for (message, count) in compile_errors.into_iter().take(TOP_ERRORS) {
+        println!("  {} {}", count, message);
+    }
+    Ok(())
+}
+
+// judges a program on every small input instead of a sample, which proves a
+// jury solution right on them and makes a brutal stress test
+fn exhaustive(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let range = options.get("range").unwrap_or("10").parse::<i64>()?;
+    let max_n = options.get("max-n").unwrap_or("6").parse::<usize>()?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let program = read_program(filename)?;
+    let task = options.task(program.as_ref().ok())?;
+    let program = match program.and_then(|program| program.check_banned(task)) {
+        Ok(program) => program,
+        Err(compile_error) => {
+            println!("{}", compile_error);
+            return Ok(());
+        }
+    };
+    let plan = load_test_plan(task, options.get("pack"))?;
+    let tests = exhaustive_tests(task, range, max_n)?;
+    for test in &tests {
+        plan.format.validate(&test.input).map_err(|message| {
+            CheckerFail(format!("{} doesn't fit the task: {}", test.params, message))
+        })?;
+    }
+    eprintln!("checking {} inputs", tests.len());
+    for test in &tests {
+        let mut program = program.clone();
+        let verdict = test.run_and_check(&mut program, plan.time_limit);
+        if !matches!(verdict, Verdict::Correct()) {
+            println!("{}\ninput:\n{}", verdict, input_text(&test.input));
+            return Ok(());
+        }
+    }
+    println!("all {} inputs passed", tests.len());
+    Ok(())
+}
+
+// runs a program over every test of a plan and fits its worst cost per input
+// size to c * n^k, to see how far it is from the limit and where it would hit it
+fn budget(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let mut program = match read_program(filename)? {
+        Ok(program) => program,
+        Err(compile_error) => {
+            println!("{}", compile_error);
+            return Ok(());
+        }
+    };
+    // far enough past the limit to see how the cost grows, but a program that
+    // never stops still gets stopped
+    const LIMIT_MULTIPLE: usize = 10;
+    let task = options.task(Some(&program))?;
+    let plan = load_test_plan(task, options.get("pack"))?;
+    program.strip_for_judging();
+    let pristine = program.snapshot();
+    let mut worst = BTreeMap::new();
+    let mut failed = 0;
+    for (_, test) in plan.tests() {
+        program.restore(&pristine);
+        let verdict =
+            test.run_and_check(&mut program, plan.time_limit.saturating_mul(LIMIT_MULTIPLE));
+        failed += !matches!(verdict, Verdict::Correct()) as usize;
+        let cost = worst.entry(test.size()).or_insert(0);
+        *cost = program.runtime().max(*cost);
+    }
+    println!("time limit: {}", plan.time_limit);
+    for (n, cost) in &worst {
+        println!(
+            "n = {}: worst cost {} ({}% of limit)",
+            n,
+            cost,
+            cost * 100 / plan.time_limit.max(1)
+        );
+    }
+    if failed > 0 {
+        println!(
+            "{} tests weren't AC, so their costs may not mean much",
+            failed
+        );
     }
-    fn get_output(&mut self) -> Option<Variable> {
-        self.output.pop_front()
+    // least squares on log cost against log n
+    let points = worst
+        .iter()
+        .filter(|&(&n, &cost)| n > 0 && cost > 0)
+        .map(|(&n, &cost)| ((n as f64).ln(), (cost as f64).ln()))
+        .collect::<Vec<_>>();
+    if points.len() < 2 {
+        println!("not enough different n to tell how the cost scales");
+        return Ok(());
     }
-    fn has_output(&self) -> bool {
-        !self.output.is_empty()
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let k = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>()
+        / points
+            .iter()
+            .map(|(x, _)| (x - mean_x).powi(2))
+            .sum::<f64>();
+    let c = (mean_y - k * mean_x).exp();
+    println!("cost is about {:.1} * n^{:.2}", c, k);
+    if k > 0.0 {
+        println!(
+            "that hits the limit around n = {:.0}",
+            (plan.time_limit as f64 / c).powf(1.0 / k)
+        );
     }
-    fn execute_one(&mut self) -> Result<(), Verdict> {
-        let cur_pc = self.pc;
-        let mut next_pc = cur_pc + 1;
-        let Some(instruction) = self.instructions.get(cur_pc) else {
-            return Err(Verdict::RuntimeError(
-                cur_pc,
-                format!("that's not even a line"),
-            ));
-        };
-        self.runtime = self.runtime.saturating_add(self.costs[cur_pc]);
-        match instruction.clone() {
-            Instruction::Nop() => {}
-            Instruction::Input(dst) => {
-                let Operand::Variable(var) = dst else {
-                    return Err(Verdict::RuntimeError(
-                        cur_pc,
-                        format!("input operand must be an identifier"),
-                    ));
-                };
-                let Some(input) = self.input.pop_front() else {
-                    return Err(Verdict::RuntimeError(
-                        cur_pc,
-                        format!("you're reading from nothing"),
-                    ));
-                };
-                self.variables.insert(var.clone(), input);
-            }
-            Instruction::Output(src) => {
-                if let Operand::Variable(ref var) = src {
-                    let Some(value) = self.variables.get(var) else {
-                        return Err(Verdict::RuntimeError(
-                            cur_pc,
-                            format!("you're printing nothing"),
-                        ));
-                    };
-                    self.output.push_back(value.clone());
-                } else {
-                    let output = self.get_value(&src)?;
-                    self.output.push_back(Variable::Integer(output));
-                }
-            }
-            Instruction::Assign(dst, src) => {
-                *self.get_reference_mut(&dst)? = self.get_value(&src)?;
-            }
-            Instruction::Add(dst, src) => {
-                *self.get_reference_mut(&dst)? += self.get_value(&src)?;
+    if let Some(n) = options.get("n") {
+        let n = n.parse::<usize>()?;
+        let cost = c * (n as f64).powf(k);
+        println!(
+            "at n = {} expect a cost of about {:.0}, which {}",
+            n,
+            cost,
+            if cost <= plan.time_limit as f64 {
+                "fits"
+            } else {
+                "TLEs"
             }
-            Instruction::Sub(dst, src) => {
-                *self.get_reference_mut(&dst)? -= self.get_value(&src)?;
+        );
+    }
+    Ok(())
+}
+
+// runs programs once and prices every executed line under two cost models,
+// to see what a change to the cost model would do to real submissions
+fn costdiff(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let model = |name: &str| {
+        COST_MODELS
+            .iter()
+            .find(|(model, _)| *model == name)
+            .map(|&(_, cost_model)| cost_model)
+            .ok_or_else(|| CheckerFail(format!("no cost model called '{}'", compress(name))))
+    };
+    let from = model(options.get("from").unwrap_or("line"))?;
+    let to = model(options.require("to")?)?;
+    let task = options.require("task")?.parse()?;
+    let plan = load_test_plan(task, options.get("pack"))?;
+    if options.positional.is_empty() {
+        return Err(CheckerFail("expected some program files".to_string()));
+    }
+    for filename in &options.positional {
+        let lines = read_lines(filename)?;
+        let (mut program, mut candidate) = match (
+            Program::compile_with(&lines, from, &[]),
+            Program::compile_with(&lines, to, &[]),
+        ) {
+            (Ok(program), Ok(candidate)) => (program, candidate),
+            (Err(compile_error), _) | (_, Err(compile_error)) => {
+                println!("{}: {}", filename, compile_error);
+                continue;
             }
-            Instruction::Compare(dst, src) => {
-                let dst = self.get_value(&dst)?;
-                let src = self.get_value(&src)?;
-                if !(dst > src) {
-                    next_pc = cur_pc + 2;
+        };
+        program.strip_for_judging();
+        candidate.strip_for_judging();
+        let pristine = program.snapshot();
+        // the cost model doesn't change where a program goes, so counting
+        // how often each line runs is enough to price it under both
+        let mut hits = vec![0usize; lines.len()];
+        let mut worst = (0, 0);
+        for (_, test) in plan.tests() {
+            program.restore(&pristine);
+            test.prepare(&mut program);
+            let mut test_hits = vec![0usize; lines.len()];
+            while !program.returned && program.runtime() <= plan.time_limit {
+                if let Some(count) = test_hits.get_mut(program.pc()) {
+                    *count += 1;
+                }
+                if program.execute_one().is_err() {
+                    break;
                 }
             }
-            Instruction::Jump(dst) => {
-                let Operand::Constant(line) = dst else {
-                    return Err(Verdict::RuntimeError(
-                        cur_pc,
-                        format!("simp operand must be a constant"),
-                    ));
-                };
-                next_pc = line
-                    .checked_sub(1)
-                    .and_then(|line| line.try_into().ok())
-                    .ok_or_else(|| {
-                        Verdict::RuntimeError(cur_pc, format!("simp operand must be positive"))
-                    })?;
+            let cost = |costs: &[usize]| {
+                test_hits
+                    .iter()
+                    .zip(costs)
+                    .map(|(hits, cost)| hits * cost)
+                    .sum::<usize>()
+            };
+            let (before, after) = (cost(&program.code.costs), cost(&candidate.code.costs));
+            worst = (worst.0.max(before), worst.1.max(after));
+            hits.iter_mut()
+                .zip(&test_hits)
+                .for_each(|(total, hits)| *total += hits);
+        }
+        let total = |costs: &[usize]| {
+            hits.iter()
+                .zip(costs)
+                .map(|(hits, cost)| hits * cost)
+                .sum::<usize>()
+        };
+        let (before, after) = (total(&program.code.costs), total(&candidate.code.costs));
+        println!(
+            "{}: total {} -> {} ({:+.1}%), worst test {} -> {}{}",
+            filename,
+            before,
+            after,
+            (after as f64 - before as f64) * 100.0 / before.max(1) as f64,
+            worst.0,
+            worst.1,
+            if worst.1 > plan.time_limit && worst.0 <= plan.time_limit {
+                ", which would TLE"
+            } else {
+                ""
             }
-            Instruction::Return() => {
-                self.returned = true;
+        );
+        if options.positional.len() == 1 {
+            let len = program.code.instructions.len();
+            for (pc, &runs) in hits.iter().enumerate().take(len) {
+                let (before, after) = (
+                    runs * program.code.costs[pc],
+                    runs * candidate.code.costs[pc],
+                );
+                if before != after {
+                    let lineno = program.code.source_line(pc);
+                    println!(
+                        "  line {} ({} runs): {} -> {}  {}",
+                        lineno + 1,
+                        runs,
+                        before,
+                        after,
+                        lines[lineno]
+                    );
+                }
             }
         }
-        self.pc = next_pc;
-        Ok(())
     }
-    fn execute(&mut self, time_limit: usize) -> Result<(), Verdict> {
-        loop {
-            if self.returned {
-                return Ok(());
-            }
-            if self.runtime > time_limit {
-                return Err(Verdict::TimeLimitExceeded());
+    Ok(())
+}
+
+// judges the same program several times on separate threads, alternating
+// between fresh states and restored snapshots, and regenerating the tests
+// each time, so anything nondeterministic shows up as a difference
+fn flaky(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let runs = options.get("runs").unwrap_or("4").parse::<usize>()?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let mut program = match read_program(filename)? {
+        Ok(program) => program,
+        Err(compile_error) => {
+            println!("{}", compile_error);
+            return Ok(());
+        }
+    };
+    let task = options.task(Some(&program))?;
+    program.strip_for_judging();
+    let handles = (0..runs)
+        .map(|run| {
+            let code = program.code.clone();
+            std::thread::spawn(move || -> Result<Vec<String>, CheckerFail> {
+                let plan = test_plan(task, DEFAULT_SEED)?;
+                let mut program = Program::spawn(code.clone());
+                let pristine = program.snapshot();
+                Ok(plan
+                    .tests()
+                    .map(|(_, test)| {
+                        if run % 2 == 0 {
+                            program.restore(&pristine);
+                        } else {
+                            program = Program::spawn(code.clone());
+                        }
+                        let verdict = test.run_and_check(&mut program, plan.time_limit);
+                        format!(
+                            "{} {}, cost {}, output {:?}",
+                            verdict.code(),
+                            verdict.message(),
+                            program.runtime(),
+                            program.output
+                        )
+                    })
+                    .collect())
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut results = Vec::new();
+    for handle in handles {
+        match handle.join() {
+            Ok(result) => results.push(result?),
+            Err(_) => return Err(CheckerFail("a run panicked".to_string())),
+        }
+    }
+    let mut differences = 0;
+    for (run, result) in results.iter().enumerate().skip(1) {
+        for (index, (expected, actual)) in results[0].iter().zip(result).enumerate() {
+            if expected != actual {
+                differences += 1;
+                println!("test #{}, run {}: {}", index + 1, run + 1, actual);
+                println!("test #{}, run 1: {}", index + 1, expected);
             }
-            self.execute_one()?;
         }
     }
+    println!(
+        "{} differences over {} runs of {} tests",
+        differences,
+        runs,
+        results.first().map_or(0, Vec::len)
+    );
+    Ok(())
 }
 
-struct Pcg128 {
-    state: u128,
-    increment: u128,
+// one line per test with its verdict and a hash of its output, which is
+// enough to notice a change without storing every output
+fn baseline_line(id: usize, verdict: &Verdict, output: &[Variable]) -> String {
+    let output = output
+        .iter()
+        .map(|value| format!("{}\n", value))
+        .collect::<String>();
+    format!(
+        "{} {} {:016x} {}",
+        id,
+        verdict.code(),
+        fnv1a(output.bytes()),
+        verdict.message()
+    )
+    .trim_end()
+    .to_string()
 }
 
-impl Pcg128 {
-    const MULTIPLIER: u128 = 0x2360ed051fc65da44385df649fccf645;
-    fn new(state: u128, stream: u128) -> Self {
-        let increment = (stream << 1) | 1;
-        Pcg128 { state, increment }
-    }
-    fn mix(state: u128) -> u64 {
-        const SHIFT: u32 = 64;
-        const ROTATE: u32 = 122;
-        let rot = (state >> ROTATE) as u32;
-        let xsh = ((state >> SHIFT) as u64) ^ (state as u64);
-        xsh.rotate_right(rot)
-    }
-    fn next(&mut self) -> u64 {
-        self.state = self
-            .state
-            .wrapping_mul(Self::MULTIPLIER)
-            .wrapping_add(self.increment);
-        Self::mix(self.state)
+fn baseline(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let stored = match (options.get("save"), options.get("against")) {
+        (Some(_), None) => None,
+        (None, Some(path)) => Some(std::fs::read_to_string(path)?),
+        _ => {
+            return Err(CheckerFail(
+                "expected one of --save or --against".to_string(),
+            ))
+        }
+    };
+    let program = read_program(filename)?;
+    let task = options.task(program.as_ref().ok())?;
+    let program = program.and_then(|program| program.check_banned(task));
+    let plan = load_test_plan(task, options.get("pack"))?;
+    let selected = plan.select(&TestFilter::from_options(&options))?;
+    let mut lines = Vec::new();
+    for &(id, test) in &selected {
+        let line = match &program {
+            Ok(program) => {
+                let mut program = program.clone();
+                let verdict = test.run_and_check(&mut program, plan.time_limit);
+                baseline_line(id, &verdict, &program.output)
+            }
+            Err(compile_error) => baseline_line(id, compile_error, &[]),
+        };
+        if let Some(stored) = &stored {
+            let prefix = format!("{} ", id);
+            let before = stored.lines().find(|line| line.starts_with(&prefix));
+            if before != Some(&line) {
+                println!("test #{} differs from the baseline", id);
+                println!(
+                    "  before: {}",
+                    before.map_or("not in the baseline", |line| &line[prefix.len()..])
+                );
+                println!("  now:    {}", &line[prefix.len()..]);
+                return Ok(());
+            }
+        }
+        lines.push(line);
     }
-    fn next_signed(&mut self, bits: u32) -> i64 {
-        self.next() as i64 >> (64 - bits)
+    match options.get("save") {
+        Some(path) => {
+            std::fs::write(
+                path,
+                lines
+                    .iter()
+                    .map(|line| format!("{}\n", line))
+                    .collect::<String>(),
+            )?;
+            println!("saved {} tests to {}", lines.len(), path);
+        }
+        None => println!("all {} tests match the baseline", lines.len()),
     }
+    Ok(())
 }
 
-trait Task {
-    fn prepare_test_case(&self, program: &mut Program, rng: &mut Pcg128) -> i64;
-    fn run_and_check(&self, mut program: Program, rng: &mut Pcg128, time_limit: usize) -> Verdict {
-        let answer = self.prepare_test_case(&mut program, rng);
-        if let Err(error) = program.execute(time_limit) {
-            return error;
+// what the watched variables were set to and on which lines
+struct VariableHistory {
+    // with the value each one had after the last line
+    watched: Vec<(Symbol, Option<Variable>)>,
+    changes: Vec<(usize, Symbol, Variable)>,
+}
+
+impl VariableHistory {
+    // a comma separated list of names
+    fn new(names: &str) -> VariableHistory {
+        VariableHistory {
+            watched: names
+                .split(',')
+                .filter(|name| !name.is_empty())
+                .map(|name| (Symbol::intern(name), None))
+                .collect(),
+            changes: Vec::new(),
         }
-        match program.get_output() {
-            Some(Variable::Integer(output)) => {
-                if program.has_output() {
-                    Verdict::WrongAnswer(format!("too much stuff printed"))
-                } else if output != answer {
-                    Verdict::WrongAnswer(format!("git gud"))
-                } else {
-                    Verdict::Correct()
+    }
+    // call after every line with the pc of the line that just ran
+    fn record(&mut self, pc: usize, program: &Program) {
+        for (name, last) in &mut self.watched {
+            let value = program.variable(*name);
+            if value != last.as_ref() {
+                *last = value.cloned();
+                if let Some(value) = value {
+                    self.changes.push((pc, *name, value.clone()));
                 }
             }
-            Some(_) => Verdict::WrongAnswer(format!("U PRINTERD AN ENTRIE ARRAY???")),
-            None => Verdict::WrongAnswer(format!("print something")),
         }
     }
+    fn of(&self, name: Symbol) -> impl Iterator<Item = (usize, &Variable)> {
+        self.changes
+            .iter()
+            .filter(move |(_, changed, _)| *changed == name)
+            .map(|(pc, _, value)| (*pc, value))
+    }
 }
 
-struct Task1();
-
-impl Task for Task1 {
-    fn prepare_test_case(&self, program: &mut Program, rng: &mut Pcg128) -> i64 {
-        let a = rng.next_signed(60);
-        let b = rng.next_signed(60);
-        program.add_input(Variable::Integer(a));
-        program.add_input(Variable::Integer(b));
-        a + b
+// the input to run on instead of a test from the plan: `--input-file` in the
+// task's input format, with `-` for stdin, or the values themselves given in
+// order with `--input 5 --input-array 1,2,3 --input-text hello`
+fn custom_input(
+    options: &Options,
+    format: &InputFormat,
+) -> Result<Option<Vec<Variable>>, CheckerFail> {
+    use std::io::IsTerminal;
+    if let Some(filename) = options.get("input-file") {
+        let text = if filename == "-" {
+            if std::io::stdin().is_terminal() {
+                eprintln!("type the input ({}), then ctrl-d", format);
+            }
+            std::io::read_to_string(std::io::stdin())?
+        } else {
+            std::fs::read_to_string(filename)?
+        };
+        return format
+            .read(&text)
+            .map(Some)
+            .map_err(|message| CheckerFail(format!("{}: {}", filename, message)));
+    }
+    let mut input = Vec::new();
+    for (name, value) in &options.named {
+        let value = value.as_deref().unwrap_or("");
+        match name.as_str() {
+            "input" => input.push(Variable::Integer(value.parse()?)),
+            "input-array" => input.push(Variable::Array(
+                value
+                    .split(',')
+                    .filter(|value| !value.is_empty())
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()?,
+            )),
+            "input-text" => input.push(Variable::Text(value.to_string())),
+            // rows separated by `;`
+            "input-grid" => input.push(Variable::Grid(
+                value
+                    .split(';')
+                    .filter(|row| !row.is_empty())
+                    .map(|row| row.split(',').map(str::parse).collect())
+                    .collect::<Result<_, _>>()?,
+            )),
+            _ => {}
+        }
     }
+    Ok(Some(input).filter(|input| !input.is_empty()))
 }
 
-struct Task2();
+// how run prints what a program yeeted
+enum OutputFormat {
+    // one value per line, arrays in brackets
+    Plain,
+    // the format input files are in, so it can be fed to another program
+    Input,
+    Json,
+}
 
-impl Task for Task2 {
-    fn prepare_test_case(&self, program: &mut Program, rng: &mut Pcg128) -> i64 {
-        let a = rng.next_signed(60);
-        program.add_input(Variable::Integer(a));
-        a.abs()
+impl OutputFormat {
+    fn from_options(options: &Options) -> Result<Self, CheckerFail> {
+        match options.get("output").unwrap_or("plain") {
+            "plain" => Ok(OutputFormat::Plain),
+            "input" => Ok(OutputFormat::Input),
+            "json" => Ok(OutputFormat::Json),
+            other => Err(CheckerFail(format!(
+                "unknown output format {}, expected plain, input or json",
+                compress(other)
+            ))),
+        }
+    }
+    fn render(&self, outputs: &[Variable]) -> String {
+        let values = outputs.iter().map(Variable::to_string).collect::<Vec<_>>();
+        match self {
+            OutputFormat::Plain => values.iter().map(|value| format!("{}\n", value)).collect(),
+            OutputFormat::Input if outputs.is_empty() => String::new(),
+            OutputFormat::Input => format!("{}\n", input_text(outputs)),
+            // arrays already print as json arrays, text doesn't print as a
+            // json string
+            OutputFormat::Json => format!(
+                "{{\"schema_version\":{},\"outputs\":[{}]}}\n",
+                JSON_SCHEMA_VERSION,
+                outputs
+                    .iter()
+                    .map(|output| match output {
+                        Variable::Text(text) => json_string(text),
+                        output => output.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        }
     }
 }
 
-struct Task3(usize);
+// the custom input if there is one, otherwise the `--test` from the plan
+fn chosen_test(options: &Options, plan: &TestPlan, task: i32) -> Result<TestCase, CheckerFail> {
+    let index = options.get("test").unwrap_or("1").parse::<usize>()?;
+    match custom_input(options, &plan.format)? {
+        Some(input) => {
+            plan.format
+                .validate(&input)
+                .map_err(|message| CheckerFail(format!("input: {}", message)))?;
+            external_test(task, input)
+        }
+        None => match index
+            .checked_sub(1)
+            .and_then(|index| plan.tests().nth(index))
+        {
+            Some((_, test)) => Ok(test.clone()),
+            None => Err(CheckerFail(format!("no test #{} for task {}", index, task))),
+        },
+    }
+}
 
-impl Task for Task3 {
-    fn prepare_test_case(&self, program: &mut Program, rng: &mut Pcg128) -> i64 {
-        let n = self.0;
-        let mut a = Vec::new();
-        a.resize_with(n, || rng.next_signed(60));
-        let answer = *a.iter().max().unwrap();
-        program.add_input(Variable::Integer(n as i64));
-        program.add_input(Variable::Array(a));
-        answer
+fn run(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &["variables", "strict"])?;
+    let output_format = OutputFormat::from_options(&options)?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let program =
+        read_program(filename)?.and_then(|program| program.check_result(options.has("strict")));
+    let task = options.task(program.as_ref().ok())?;
+    let mut program = match program.and_then(|program| program.check_banned(task)) {
+        Ok(program) => program,
+        Err(compile_error) => {
+            println!("{}", compile_error);
+            return Ok(());
+        }
+    };
+    let plan = load_test_plan(task, options.get("pack"))?;
+    let test = chosen_test(&options, &plan, task)?;
+    test.prepare(&mut program);
+    let mut history = VariableHistory::new(options.get("watch").unwrap_or(""));
+    let mut ran = program.pc();
+    let result = program.execute_with(plan.time_limit, |program| {
+        history.record(ran, program);
+        ran = program.pc();
+        Ok(())
+    });
+    for (line, value) in &program.debug_output {
+        eprintln!("line {}: {}", program.code.source_line(*line) + 1, value);
     }
+    eprintln!(
+        "cost {}, peak memory {} bytes",
+        program.runtime(),
+        program.memory.peak
+    );
+    for &(name, _) in &history.watched {
+        let changes = history
+            .of(name)
+            .map(|(pc, value)| format!("{} on line {}", value, program.code.source_line(pc) + 1))
+            .collect::<Vec<_>>();
+        eprintln!("{}: {}", name, changes.join(", "));
+    }
+    if options.has("variables") {
+        let line = program.code.source_line(program.pc()) + 1;
+        match program.instruction_at(program.pc()) {
+            Some(instruction) => eprintln!("next up is line {}: {}", line, instruction),
+            None => eprintln!("next up is line {}", line),
+        }
+        for (name, value) in program.variables() {
+            eprintln!("{} = {}", name, value);
+        }
+    }
+    print!("{}", output_format.render(program.peek_outputs()));
+    let verdict = match result {
+        Ok(()) => test.check(&mut program),
+        Err(error) => error,
+    };
+    // keeps stdout clean for whatever reads the output
+    match output_format {
+        OutputFormat::Plain => println!("{}", verdict),
+        _ => eprintln!("{}", verdict),
+    }
+    Ok(())
 }
 
-struct Task4(usize);
+// runs lines as they are typed, keeping the variables between them, on the
+// input of a test like `run`. each line gets the task's time limit
+fn repl(args: &[String]) -> Result<(), CheckerFail> {
+    use std::io::IsTerminal;
+    let options = Options::parse(args, &[])?;
+    let task = options.task(None)?;
+    let plan = load_test_plan(task, options.get("pack"))?;
+    let test = chosen_test(&options, &plan, task)?;
+    let mut program = Program::spawn(Arc::default());
+    test.prepare(&mut program);
+    program.time_limit = plan.time_limit;
+    if std::io::stdin().is_terminal() {
+        eprintln!("type lines of code, ctrl-d to stop");
+    }
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match eval_instruction(&mut program, &line) {
+            Ok(outcome) => {
+                for output in program.drain_outputs() {
+                    println!("{}", output);
+                }
+                if let StepOutcome::Returned() = outcome {
+                    eprintln!("touched grass, cost {}", program.runtime());
+                    break;
+                }
+            }
+            Err(verdict) => eprintln!("{}", verdict),
+        }
+    }
+    Ok(())
+}
 
-impl Task for Task4 {
-    fn prepare_test_case(&self, program: &mut Program, rng: &mut Pcg128) -> i64 {
-        let n = self.0;
-        let mut a = Vec::new();
-        let k = rng.next() as usize % n + 1;
-        a.resize_with(n, || rng.next_signed(60));
-        let answer = *a.clone().select_nth_unstable(n - k).1;
-        program.add_input(Variable::Integer(n as i64));
-        program.add_input(Variable::Array(a));
-        program.add_input(Variable::Integer(k as i64));
-        answer
+// runs programs one after another, each reading what the previous one
+// yeeted, with the time limit of the first program's task shared by all
+fn pipe(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let output_format = OutputFormat::from_options(&options)?;
+    if options.positional.len() < 2 {
+        return Err(CheckerFail(
+            "expected at least two program files".to_string(),
+        ));
+    }
+    let mut programs = Vec::new();
+    for filename in &options.positional {
+        match read_program(filename)? {
+            Ok(program) => programs.push(program),
+            Err(compile_error) => {
+                println!("{}: {}", filename, compile_error);
+                return Ok(());
+            }
+        }
+    }
+    let task = options.task(programs.first())?;
+    let plan = load_test_plan(task, options.get("pack"))?;
+    let mut input = chosen_test(&options, &plan, task)?.input;
+    let mut total = 0;
+    for (filename, mut program) in options.positional.iter().zip(programs) {
+        program.feed_inputs(input);
+        let result = program.execute(plan.time_limit.saturating_sub(total));
+        total += program.runtime();
+        eprintln!("{}: cost {}", filename, program.runtime());
+        if let Err(verdict) = result {
+            eprintln!("total cost {} of {}", total, plan.time_limit);
+            println!("{}: {}", filename, verdict);
+            return Ok(());
+        }
+        input = program.drain_outputs();
     }
+    eprintln!("total cost {} of {}", total, plan.time_limit);
+    print!("{}", output_format.render(&input));
+    Ok(())
 }
 
-fn judge(task: i32, filename: &str) -> Result<Verdict, CheckerFail> {
-    let reader = BufReader::new(std::fs::File::open(filename)?);
-    let program = match Program::compile(&reader.lines().collect::<Result<Vec<_>, _>>()?) {
+fn inspect(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let program = match read_program(filename)? {
         Ok(program) => program,
-        Err(compile_error) => return Ok(compile_error),
+        Err(compile_error) => {
+            println!("{}", compile_error);
+            return Ok(());
+        }
     };
-    let mut rng = Pcg128::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
-    match task {
-        1 => {
-            for _ in 0..10 {
-                match Task1().run_and_check(program.clone(), &mut rng, 100000) {
-                    Verdict::Correct() => continue,
-                    verdict => return Ok(verdict),
-                }
+    let metadata = program.metadata();
+    if let Some(author) = &metadata.author {
+        println!("author: {}", author);
+    }
+    if let Some(task) = metadata.task {
+        println!("task: {}", task);
+    }
+    if let Some(version) = metadata.version {
+        println!("version: {}", version);
+    }
+    if !metadata.features.is_empty() {
+        println!("needs: {}", metadata.features.join(", "));
+    }
+    let mut variables = BTreeSet::new();
+    let mut arrays = BTreeSet::new();
+    for operand in program
+        .code
+        .instructions
+        .iter()
+        .flat_map(Instruction::operands)
+        .flat_map(|operand| operand.flatten())
+    {
+        match operand {
+            Operand::Constant(_) | Operand::Text(_) => {}
+            Operand::Variable(var) => {
+                variables.insert(var.name());
             }
-        }
-        2 => {
-            for _ in 0..10 {
-                match Task2().run_and_check(program.clone(), &mut rng, 100000) {
-                    Verdict::Correct() => continue,
-                    verdict => return Ok(verdict),
+            Operand::ArrayConstIndex(array, _) | Operand::ArrayNestedIndex(array, _) => {
+                arrays.insert(array.name());
+            }
+            Operand::ArrayVarIndex(array, index) => {
+                arrays.insert(array.name());
+                variables.insert(index.name());
+            }
+            Operand::GridIndex(grid, row, col) => {
+                arrays.insert(grid.name());
+                for index in [row, col] {
+                    if let Index::Variable(var) = index {
+                        variables.insert(var.name());
+                    }
                 }
             }
         }
-        3 => {
-            for n in 1..=50 {
-                match Task3(n).run_and_check(program.clone(), &mut rng, 100000) {
-                    Verdict::Correct() => continue,
-                    verdict => return Ok(verdict),
-                }
+    }
+    println!("instructions: {}", program.code.instructions.len());
+    println!("static cost: {}", program.code.costs.iter().sum::<usize>());
+    println!("semantic hash: {:016x}", program.semantic_hash());
+    println!(
+        "variables: {}",
+        variables.into_iter().collect::<Vec<_>>().join(", ")
+    );
+    println!(
+        "arrays: {}",
+        arrays.into_iter().collect::<Vec<_>>().join(", ")
+    );
+    let jumps = program
+        .code
+        .instructions
+        .iter()
+        .enumerate()
+        .filter_map(|(pc, instruction)| match instruction {
+            Instruction::Jump(Operand::Constant(target)) => Some((pc + 1, *target)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let computed = program
+        .code
+        .instructions
+        .iter()
+        .enumerate()
+        .filter_map(|(pc, instruction)| match instruction {
+            Instruction::Jump(Operand::Constant(_)) => None,
+            Instruction::Jump(target) => Some((pc + 1, target)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    println!(
+        "jumps: {} ({} backward, {} computed)",
+        jumps.len() + computed.len(),
+        jumps
+            .iter()
+            .filter(|&&(line, target)| target <= line as i64)
+            .count(),
+        computed.len()
+    );
+    for (line, target) in jumps {
+        println!("  line {} -> line {}", line, target);
+    }
+    for (line, target) in computed {
+        println!("  line {} -> line {}", line, target);
+    }
+    let mut lints = program.lints();
+    if let Ok(task) = options.task(Some(&program)) {
+        lints.extend(program.bounds_lints(&test_plan(task, DEFAULT_SEED)?.format));
+    }
+    println!("lints: {}", lints.len());
+    for lint in lints {
+        println!("  {}", lint);
+    }
+    Ok(())
+}
+
+fn compare(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let [first, second] = &options.positional[..] else {
+        return Err(CheckerFail(
+            "expected exactly two program files".to_string(),
+        ));
+    };
+    let mut fingerprints = Vec::new();
+    let mut hashes = Vec::new();
+    for filename in [first, second] {
+        match read_program(filename)? {
+            Ok(program) => {
+                fingerprints.push(program.fingerprint());
+                hashes.push(program.semantic_hash());
+            }
+            Err(compile_error) => {
+                return Err(CheckerFail(format!("{}: {}", filename, compile_error)));
             }
         }
-        4 => {
-            for n in 1..=50 {
-                for _ in 0..(25 / n + 1) {
-                    match Task4(n).run_and_check(program.clone(), &mut rng, 2500000) {
-                        Verdict::Correct() => continue,
-                        verdict => return Ok(verdict),
+    }
+    let shared = fingerprints[0].intersection(&fingerprints[1]).count();
+    let total = fingerprints[0].union(&fingerprints[1]).count();
+    println!(
+        "similarity: {:.1}% ({} of {} shingles shared)",
+        100.0 * shared as f64 / total.max(1) as f64,
+        shared,
+        total
+    );
+    if hashes[0] == hashes[1] {
+        println!("same program up to renaming and spacing");
+    }
+    Ok(())
+}
+
+// the index-th shortest identifier, skipping the ones that would get the
+// program rejected
+fn short_name(index: usize) -> String {
+    const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_";
+    const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_0123456789";
+    let name = |mut index: usize| {
+        let mut name = vec![FIRST[index % FIRST.len()]];
+        index /= FIRST.len();
+        while index > 0 {
+            index -= 1;
+            name.push(REST[index % REST.len()]);
+            index /= REST.len();
+        }
+        String::from_utf8(name).unwrap()
+    };
+    (0..)
+        .map(name)
+        .filter(|name| !name.to_lowercase().contains("based"))
+        .nth(index)
+        .unwrap()
+}
+
+fn mangle(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let lines = read_lines(filename)?;
+    let program = match Program::compile(&lines) {
+        Ok(program) => program,
+        Err(compile_error) => return Err(CheckerFail(compile_error.to_string())),
+    };
+    // the most used identifiers get the shortest names
+    let mut counts = HashMap::<String, (usize, usize)>::new();
+    for operand in program
+        .code
+        .instructions
+        .iter()
+        .flat_map(Instruction::operands)
+        .flat_map(|operand| operand.flatten())
+    {
+        let mut count = |name: &str| {
+            let first_seen = counts.len();
+            counts.entry(name.to_string()).or_insert((0, first_seen)).0 += 1;
+        };
+        match operand {
+            Operand::Constant(_) | Operand::Text(_) => {}
+            Operand::Variable(var) => count(var.name()),
+            Operand::ArrayConstIndex(array, _) | Operand::ArrayNestedIndex(array, _) => {
+                count(array.name())
+            }
+            Operand::ArrayVarIndex(array, index) => {
+                count(array.name());
+                count(index.name());
+            }
+            Operand::GridIndex(grid, row, col) => {
+                count(grid.name());
+                for index in [row, col] {
+                    if let Index::Variable(var) = index {
+                        count(var.name());
                     }
                 }
             }
         }
+    }
+    let mut names = counts.into_iter().collect::<Vec<_>>();
+    names.sort_by_key(|&(_, (count, first_seen))| (std::cmp::Reverse(count), first_seen));
+    let names = names
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, _))| (name, short_name(index)))
+        .collect::<HashMap<_, _>>();
+    let mangled = program
+        .code
+        .instructions
+        .iter()
+        .enumerate()
+        .map(|(pc, instruction)| match instruction {
+            // keep header directives, just without the extra spaces and
+            // comments
+            Instruction::Nop() => strip_comment(&lines[program.code.source_line(pc)])
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            instruction => instruction
+                .map_operands(|operand| operand.map_identifiers(&mut |name| names[name].clone()))
+                .to_string(),
+        })
+        .collect::<Vec<_>>();
+    let result = match Program::compile(&mangled) {
+        Ok(result) if result.canonicalize() == program.canonicalize() => result,
         _ => {
-            return Err(CheckerFail(format!("unknown task id {}", task)));
+            return Err(CheckerFail(
+                "mangling changed what the program does".to_string(),
+            ))
         }
+    };
+    for line in &mangled {
+        println!("{}", line);
     }
-    Ok(Verdict::Correct())
+    let before = program.code.costs.iter().sum::<usize>();
+    let after = result.code.costs.iter().sum::<usize>();
+    eprintln!(
+        "static cost {} -> {}, saved {}",
+        before,
+        after,
+        before - after
+    );
+    Ok(())
 }
 
-fn work() -> Result<Verdict, CheckerFail> {
-    let argv = std::env::args().collect::<Vec<_>>();
-    let [inf, ouf, ans] = &argv[1..=3] else {
-        panic!("not enough args");
-    };
-    let task = std::fs::read_to_string(inf)?.trim().parse()?;
-    match judge(task, ans) {
-        Ok(Verdict::Correct()) => match judge(task, ouf) {
-            Ok(verdict) => Ok(verdict),
-            Err(error) => Ok(Verdict::OtherError(error.0)),
-        },
-        Ok(verdict) => Err(CheckerFail(format!(
-            "jury's solution failed with verdict {:?}",
-            verdict
-        ))),
-        Err(error) => Err(error),
-    }
+// removes lines from a program, retargeting `simp for`s and `brb`s that
+// pointed past them. targets count instructions, which from version 2 on
+// aren't the same as lines
+fn remove_lines(lines: &[String], removed: std::ops::Range<usize>) -> Vec<String> {
+    let source_lines = Program::compile(lines)
+        .map(|program| program.code.source_lines.clone())
+        .unwrap_or_default();
+    let removed_lines = removed.clone();
+    let pc_of = |lineno: usize| match &source_lines[..] {
+        [] => lineno,
+        source_lines => source_lines.iter().filter(|&&kept| kept < lineno).count(),
+    };
+    let removed = pc_of(removed.start)..pc_of(removed.end);
+    let retarget = |line: i64| {
+        let pc = (line - 1) as usize;
+        if pc < removed.start {
+            line
+        } else if pc < removed.end {
+            removed.start as i64 + 1
+        } else {
+            line - removed.len() as i64
+        }
+    };
+    lines
+        .iter()
+        .enumerate()
+        .filter(|&(lineno, _)| !removed_lines.contains(&lineno))
+        .map(|(_, line)| {
+            let code = strip_comment(line);
+            match Instruction::try_from(code) {
+                Ok(instruction) if instruction.constant_target() >= Some(1) => format!(
+                    "{}{}",
+                    instruction.retarget(retarget(instruction.constant_target().unwrap())),
+                    &line[code.len()..]
+                ),
+                _ => line.clone(),
+            }
+        })
+        .collect()
 }
 
-fn main() {
-    use Verdict::*;
-    match work() {
-        Err(CheckerFail(message)) => {
-            eprintln!("CHECKER ERROR author made the oopsie: {}", message);
-            std::process::exit(3);
-        }
-        Ok(Correct()) => {
-            eprintln!("ur the GOAT of based code!!1!",);
-            std::process::exit(0);
-        }
-        Ok(WrongAnswer(message)) => {
-            eprintln!("this ain't it, chief, {}", message);
-            std::process::exit(1);
-        }
-        Ok(TimeLimitExceeded()) => {
-            eprintln!("you have skill issue on speed smh");
-            std::process::exit(1);
-        }
-        Ok(RuntimeError(line, message)) => {
-            eprintln!(
-                "ya code got L + ratioed on line {} because {}",
-                line + 1,
-                message
-            );
-            std::process::exit(1);
+fn reduce(args: &[String]) -> Result<(), CheckerFail> {
+    let options = Options::parse(args, &[])?;
+    let [filename] = &options.positional[..] else {
+        return Err(CheckerFail("expected exactly one program file".to_string()));
+    };
+    let mut lines = read_lines(filename)?;
+    let original = Program::compile(&lines);
+    let task = options.task(original.as_ref().ok())?;
+    let mut plan = load_test_plan(task, options.get("pack"))?;
+    if let Some(index) = options.get("test") {
+        let index = index.parse::<usize>()?;
+        let Some((_, test)) = index
+            .checked_sub(1)
+            .and_then(|index| plan.tests().nth(index))
+        else {
+            return Err(CheckerFail(format!("no test #{} for task {}", index, task)));
+        };
+        plan.random = vec![test.clone()];
+        plan.corner_cases.clear();
+    }
+    let builder = || InterpreterBuilder::new().time_limit(plan.time_limit);
+    let verdict = |lines: &[String]| match builder().build(lines) {
+        Ok(mut interpreter) => plan.judge(&mut interpreter, &VerdictPrecedence::First),
+        Err(compile_error) => compile_error,
+    };
+    // without --verdict, the message has to stay the same too
+    let (target, message) = match options.get("verdict") {
+        Some(code) => (code.to_uppercase(), None),
+        None => {
+            let verdict = verdict(&lines);
+            (
+                verdict.code().to_string(),
+                Some(verdict.message().to_string()),
+            )
         }
-        Ok(CompileError(line, message)) => {
-            eprintln!(
-                "jesse, what are you talking about on line {}? {}",
-                line + 1,
-                message
-            );
-            std::process::exit(1);
+    };
+    let interesting = |lines: &[String]| {
+        let verdict = verdict(lines);
+        verdict.code() == target
+            && message
+                .as_ref()
+                .map_or(true, |message| message == verdict.message())
+    };
+    if !interesting(&lines) {
+        return Err(CheckerFail(format!(
+            "the program doesn't get {} to begin with",
+            target
+        )));
+    }
+    let original_len = lines.len();
+    // delta debugging: try removing chunks, halving their size when stuck
+    let mut chunk = ((lines.len() + 1) / 2).max(1);
+    while !lines.is_empty() {
+        let mut start = 0;
+        let mut removed_any = false;
+        while start < lines.len() {
+            let candidate = remove_lines(&lines, start..(start + chunk).min(lines.len()));
+            if interesting(&candidate) {
+                lines = candidate;
+                removed_any = true;
+            } else {
+                start += chunk;
+            }
         }
-        Ok(Based()) => {
-            eprintln!(
-                r#""Based"? Are you kidding me? I spent a decent portion of my life preparing this problem and your submission to it is "Based"? What do I have to say to you? Absolutely nothing. I couldn't be bothered to respond to such meaningless attempt at writing code. Do you want "Based" on your Codeforces profile?"#,
-            );
-            std::process::exit(1);
+        if !removed_any {
+            if chunk == 1 {
+                break;
+            }
+            chunk = (chunk + 1) / 2;
+        }
+    }
+    // then blank out whatever can be blanked out
+    for pc in 0..lines.len() {
+        if !lines[pc].is_empty() {
+            let mut candidate = lines.clone();
+            candidate[pc].clear();
+            if interesting(&candidate) {
+                lines = candidate;
+            }
+        }
+    }
+    for line in &lines {
+        println!("{}", line);
+    }
+    eprintln!(
+        "reduced {} -> {} lines, still {}",
+        original_len,
+        lines.len(),
+        target
+    );
+    Ok(())
+}
+
+type Command = fn(&[String]) -> Result<(), CheckerFail>;
+
+fn main() {
+    let argv = std::env::args().collect::<Vec<_>>();
+    let command: Option<Command> = match argv.get(1).map(String::as_str) {
+        Some("plan") => Some(plan),
+        Some("score") => Some(score),
+        Some("run") => Some(run),
+        Some("inspect") => Some(inspect),
+        Some("compare") => Some(compare),
+        Some("mangle") => Some(mangle),
+        Some("reduce") => Some(reduce),
+        Some("validate") => Some(validate),
+        Some("answer") => Some(answer),
+        Some("export-polygon") => Some(export_polygon),
+        Some("export-icpc") => Some(export_icpc),
+        Some("kattis") => Some(kattis),
+        Some("demo") => Some(demo),
+        Some("selftest") => Some(selftest),
+        Some("new-task") => Some(new_task),
+        Some("artifacts") => Some(artifacts),
+        Some("pack") => Some(pack),
+        Some("calibrate") => Some(calibrate),
+        Some("stats") => Some(stats),
+        Some("flaky") => Some(flaky),
+        Some("explain") => Some(explain),
+        Some("learn") => Some(learn),
+        Some("budget") => Some(budget),
+        Some("limits") => Some(limits),
+        Some("costdiff") => Some(costdiff),
+        Some("baseline") => Some(baseline),
+        Some("pipe") => Some(pipe),
+        Some("repl") => Some(repl),
+        Some("dry-run") => Some(dry_run),
+        Some("exhaustive") => Some(exhaustive),
+        Some("import") => Some(import),
+        Some("export-tests") => Some(export_tests),
+        Some("corpus") => Some(write_corpus),
+        _ => None,
+    };
+    if let Some(command) = command {
+        if let Err(CheckerFail(message)) = command(&argv[2..]) {
+            eprintln!("skill issue in the command line: {}", message);
+            std::process::exit(2);
         }
-        // polygon does weird things...
-        Ok(OtherError(message)) => {
-            eprintln!("unexpected error in participant output: {}", message);
-            std::process::exit(1);
+        return;
+    }
+    // too few files to be judging, so it was meant to be a command
+    if argv.len() < 4 {
+        match argv.get(1) {
+            Some(name) => eprintln!(
+                "skill issue in the command line: there's no command called {}",
+                compress(name)
+            ),
+            None => eprintln!(
+                "skill issue in the command line: expected a command, or the input, output and answer files"
+            ),
         }
+        std::process::exit(2);
     }
+    let outcome = work(&argv);
+    eprint!("{}", outcome.stderr);
+    std::process::exit(outcome.exit_code);
 }